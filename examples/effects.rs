@@ -0,0 +1,23 @@
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+use transition::{Effect, Led, Transition};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let notification = Transition::default()
+        .pending_effect(Effect::Rainbow {
+            period: Duration::from_secs(3),
+        })
+        .on_success_effect(Effect::Breathing {
+            color: Led::Green,
+            period: Duration::from_secs(4),
+        })
+        .start()?;
+    thread::sleep(Duration::from_secs(6));
+    notification.notify_success()?;
+
+    // the outcome effect is played in background
+    thread::sleep(Duration::from_secs(10));
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+use transition::Transition;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let transition = Transition::default();
+    for _ in 0..3 {
+        let notification = transition.start()?;
+        thread::sleep(Duration::from_secs(3));
+        notification.notify_success()?;
+        thread::sleep(Duration::from_secs(2));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,275 @@
+#[cfg(feature = "blink1")]
+use crate::blink1::BlinkBackend;
+use crate::error::TransitionErr;
+use crate::light::State;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use log::debug;
+use log::warn;
+use std::env;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Environment variable which enables the logging fallback when set to `log`.
+pub(crate) const FALLBACK_ENV: &str = "TRANSITION_FALLBACK";
+
+pub(crate) trait Backend: Send + Sync + Debug {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr>;
+
+    /// Returns the message which is currently displayed, if known.
+    fn current(&self) -> Option<BlinkMsg> {
+        None
+    }
+
+    /// Stores the pattern in the device and plays it in a loop, without any help from the host.
+    /// Returns `false` when the device cannot store patterns.
+    fn play_pattern(&self, _pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        Ok(false)
+    }
+
+    /// Stops the pattern started with [`play_pattern`](Backend::play_pattern()).
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+
+    /// Stores the pattern which the device plays when it's not tickled within `timeout` and
+    /// tickles it for the first time. Returns `false` when the device has no watchdog.
+    fn arm_watchdog(
+        &self,
+        _timeout: Duration,
+        _pattern: &[PatternLine],
+    ) -> Result<bool, TransitionErr> {
+        Ok(false)
+    }
+
+    /// Postpones the watchdog armed with [`arm_watchdog`](Backend::arm_watchdog()).
+    fn tickle_watchdog(&self) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+
+    /// Turns off the watchdog armed with [`arm_watchdog`](Backend::arm_watchdog()).
+    fn disarm_watchdog(&self) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+
+    /// Informs about the state of the transition, for backends which display more than colors.
+    fn state(&self, _state: State) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+
+    /// Waits until the sent commands are displayed, for backends which display them in
+    /// background.
+    fn flush(&self) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+}
+
+/// Implements the listed methods of [`Backend`] by passing the calls to the `inner` backend, for
+/// backends which wrap another one and change only some of the calls.
+macro_rules! delegate {
+    ($($method:ident),* $(,)?) => {
+        $(crate::backend::delegate!(@$method);)*
+    };
+    (@current) => {
+        fn current(&self) -> Option<crate::msg::BlinkMsg> {
+            self.inner.current()
+        }
+    };
+    (@play_pattern) => {
+        fn play_pattern(
+            &self,
+            pattern: &[crate::pattern::PatternLine],
+        ) -> Result<bool, crate::error::TransitionErr> {
+            self.inner.play_pattern(pattern)
+        }
+    };
+    (@stop_pattern) => {
+        fn stop_pattern(&self) -> Result<(), crate::error::TransitionErr> {
+            self.inner.stop_pattern()
+        }
+    };
+    (@arm_watchdog) => {
+        fn arm_watchdog(
+            &self,
+            timeout: std::time::Duration,
+            pattern: &[crate::pattern::PatternLine],
+        ) -> Result<bool, crate::error::TransitionErr> {
+            self.inner.arm_watchdog(timeout, pattern)
+        }
+    };
+    (@tickle_watchdog) => {
+        fn tickle_watchdog(&self) -> Result<(), crate::error::TransitionErr> {
+            self.inner.tickle_watchdog()
+        }
+    };
+    (@disarm_watchdog) => {
+        fn disarm_watchdog(&self) -> Result<(), crate::error::TransitionErr> {
+            self.inner.disarm_watchdog()
+        }
+    };
+    (@state) => {
+        fn state(&self, state: crate::light::State) -> Result<(), crate::error::TransitionErr> {
+            self.inner.state(state)
+        }
+    };
+    (@flush) => {
+        fn flush(&self) -> Result<(), crate::error::TransitionErr> {
+            self.inner.flush()
+        }
+    };
+}
+
+pub(crate) use delegate;
+
+/// Used when there is no blink(1) connected. Only logs the messages.
+#[derive(Debug, Default)]
+pub(crate) struct LogBackend;
+
+impl Backend for LogBackend {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        debug!("no device connected, skipping message: {:?}", msg);
+        Ok(())
+    }
+}
+
+/// Remembers the last message sent to the wrapped backend, so it can be restored later.
+#[derive(Debug)]
+pub(crate) struct Tracked {
+    inner: Arc<dyn Backend>,
+    last: Mutex<Option<BlinkMsg>>,
+}
+
+impl Tracked {
+    pub(crate) fn new(inner: Arc<dyn Backend>) -> Self {
+        Self {
+            inner,
+            last: Mutex::new(None),
+        }
+    }
+}
+
+impl Backend for Tracked {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        self.inner.send(msg)?;
+        *self.last.lock().expect("poisoned lock") = Some(msg);
+        Ok(())
+    }
+
+    fn current(&self) -> Option<BlinkMsg> {
+        self.inner
+            .current()
+            .or(*self.last.lock().expect("poisoned lock"))
+    }
+
+    delegate!(
+        play_pattern,
+        stop_pattern,
+        arm_watchdog,
+        tickle_watchdog,
+        disarm_watchdog,
+        state,
+        flush,
+    );
+}
+
+/// Opens the blink(1) device. Falls back to [`LogBackend`] when the device is missing and
+/// `fallback` is set (or [`FALLBACK_ENV`] is set to `log`).
+pub(crate) fn open(fallback: bool) -> Result<Arc<dyn Backend>, TransitionErr> {
+    let backend: Arc<dyn Backend> = match open_device() {
+        Ok(backend) => backend,
+        Err(e) if fallback || fallback_from_env() => {
+            warn!("blink(1) not available ({}), falling back to logging", e);
+            Arc::new(LogBackend)
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(Arc::new(Tracked::new(backend)))
+}
+
+#[cfg(feature = "blink1")]
+fn open_device() -> Result<Arc<dyn Backend>, TransitionErr> {
+    Ok(Arc::new(BlinkBackend::new()?))
+}
+
+#[cfg(not(feature = "blink1"))]
+fn open_device() -> Result<Arc<dyn Backend>, TransitionErr> {
+    Err(TransitionErr::DeviceNotFound)
+}
+
+fn fallback_from_env() -> bool {
+    fallback_from(env::var(FALLBACK_ENV).ok().as_deref())
+}
+
+/// Whether the value of [`FALLBACK_ENV`] asks for the fallback.
+fn fallback_from(value: Option<&str>) -> bool {
+    value.is_some_and(|value| value.eq_ignore_ascii_case("log"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::brightness::Dimmed;
+    use crate::calibration::Calibrated;
+    use crate::calibration::Calibration;
+    use crate::testutils::utils::init_logging;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_open_with_fallback_never_fails() {
+        init_logging();
+        let backend = open(true);
+
+        assert!(backend.is_ok(), "Fallback backend was not created");
+    }
+
+    #[test]
+    fn test_fallback_is_selected_by_env_value() {
+        assert!(fallback_from(Some("log")));
+        assert!(fallback_from(Some("LOG")));
+        assert!(!fallback_from(Some("none")));
+        assert!(!fallback_from(None));
+    }
+
+    #[derive(Debug, Default)]
+    struct FlushSpy {
+        flushed: AtomicBool,
+    }
+
+    impl Backend for FlushSpy {
+        fn send(&self, _msg: BlinkMsg) -> Result<(), TransitionErr> {
+            Ok(())
+        }
+
+        fn flush(&self) -> Result<(), TransitionErr> {
+            self.flushed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_wrapping_backends_forward_flush() -> Result<(), TransitionErr> {
+        let spy = Arc::new(FlushSpy::default());
+        let calibrated = Arc::new(Calibrated::new(spy.clone(), Calibration::default()));
+        let dimmed = Arc::new(Dimmed::new(calibrated, 0.5, None));
+
+        Tracked::new(dimmed).flush()?;
+
+        assert!(spy.flushed.load(Ordering::SeqCst), "Device was flushed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracked_remembers_last_message() -> Result<(), TransitionErr> {
+        init_logging();
+        let backend = Tracked::new(Arc::new(LogBackend));
+        assert!(backend.current().is_none(), "Nothing was sent yet");
+
+        backend.send(BlinkMsg::Off)?;
+
+        assert!(matches!(backend.current(), Some(BlinkMsg::Off)));
+        Ok(())
+    }
+}
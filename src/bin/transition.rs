@@ -0,0 +1,69 @@
+use serde::de::value::Error as DeError;
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::process;
+use transition::Led;
+
+const USAGE: &str = "Usage:
+    transition devices                      lists connected devices
+    transition startup-pattern <color>...   saves the pattern played when the device is powered
+                                            without software, e.g. `startup-pattern orange blank`
+
+Colors: red, green, blue, yellow, orange, pink, cyan, white, blank";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("devices") => list_devices(),
+        Some("startup-pattern") if args.len() > 1 => save_startup_pattern(&args[1..]),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
+
+fn list_devices() -> Result<(), Box<dyn Error>> {
+    for device in transition::devices()? {
+        let (r, g, b) = device.current_color()?;
+        println!(
+            "{}\t{:?}\tfirmware {}\tcolor #{:02x}{:02x}{:02x}",
+            device.serial(),
+            device.model(),
+            device.firmware_version(),
+            r,
+            g,
+            b
+        );
+    }
+    Ok(())
+}
+
+fn save_startup_pattern(names: &[String]) -> Result<(), Box<dyn Error>> {
+    let colors = names
+        .iter()
+        .map(|name| parse_color(name))
+        .collect::<Result<Vec<Led>, _>>()?;
+    let devices = transition::devices()?;
+    if devices.is_empty() {
+        return Err("no blink(1) device connected".into());
+    }
+    for device in devices {
+        device.save_startup_pattern(&colors)?;
+        println!("{}\tstartup pattern saved", device.serial());
+    }
+    Ok(())
+}
+
+fn parse_color(name: &str) -> Result<Led, DeError> {
+    let name = name.to_lowercase();
+    let deserializer: StrDeserializer<DeError> = name.as_str().into_deserializer();
+    Led::deserialize(deserializer)
+}
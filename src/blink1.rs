@@ -0,0 +1,372 @@
+use crate::backend::Backend;
+use crate::color;
+use crate::color::Color;
+use crate::error::TransitionErr;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use crate::pattern::MAX_LINES;
+use crate::pattern::TICK;
+use crate::transport::Report;
+use crate::transport::Transport;
+use crate::transport::UsbTransport;
+use crate::transport::REPORT_ID;
+use crate::transport::REPORT_SIZE;
+use log::warn;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Arguments of the command - everything in the report after the report id and the command.
+type Args = [u8; REPORT_SIZE - 2];
+
+/// blink(1) device, controlled with HID feature reports, see
+/// <https://github.com/todbot/blink1/blob/main/docs/blink1-hid-commands.md>.
+#[derive(Debug)]
+pub(crate) struct Blink1 {
+    transport: Arc<dyn Transport>,
+}
+
+impl Blink1 {
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Self { transport }
+    }
+
+    /// Opens all connected blink(1) devices.
+    pub(crate) fn open_all() -> Result<Vec<Self>, TransitionErr> {
+        Ok(UsbTransport::open_all()?
+            .into_iter()
+            .map(|transport| Self::new(Arc::new(transport)))
+            .collect())
+    }
+
+    /// Displays the message.
+    pub(crate) fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        match msg {
+            BlinkMsg::Off => self.command(b'n', [0; 7]),
+            BlinkMsg::Immediate(color, None) => {
+                let (r, g, b) = color::rgb(color);
+                self.command(b'n', [r, g, b, 0, 0, 0, 0])
+            }
+            BlinkMsg::Immediate(color, Some(led)) => {
+                let (r, g, b) = color::rgb(color);
+                self.command(b'c', [r, g, b, 0, 0, led, 0])
+            }
+            BlinkMsg::Fade(color, duration, led) => {
+                let (r, g, b) = color::rgb(color);
+                let [th, tl] = ticks(duration).to_be_bytes();
+                self.command(b'c', [r, g, b, th, tl, led.unwrap_or(0), 0])
+            }
+        }
+    }
+
+    /// Serial number of the device, e.g. `2000ABCD`.
+    pub(crate) fn serial(&self) -> Result<String, TransitionErr> {
+        self.transport.serial()
+    }
+
+    /// Firmware version, e.g. `204` for v2.04.
+    pub(crate) fn version(&self) -> Result<u16, TransitionErr> {
+        let report = self.query(b'v', [0; 7])?;
+        let digit = |byte: u8| u16::from(byte.saturating_sub(b'0'));
+        Ok(digit(report[3]) * 100 + digit(report[4]))
+    }
+
+    /// Color which is currently displayed.
+    pub(crate) fn read_rgb(&self) -> Result<(u8, u8, u8), TransitionErr> {
+        let report = self.query(b'r', [0; 7])?;
+        Ok((report[2], report[3], report[4]))
+    }
+
+    /// Stores the line at `position` of the pattern memory.
+    pub(crate) fn write_line(&self, position: u8, line: &PatternLine) -> Result<(), TransitionErr> {
+        let (r, g, b) = color::rgb(line.color);
+        let [th, tl] = ticks(line.fade).to_be_bytes();
+        self.command(b'P', [r, g, b, th, tl, position, 0])
+    }
+
+    /// Plays lines `0..end` of the pattern memory in a loop.
+    pub(crate) fn play(&self, end: u8) -> Result<(), TransitionErr> {
+        self.command(b'p', [1, 0, end, 0, 0, 0, 0])
+    }
+
+    /// Stops the pattern playback, the current color stays on.
+    pub(crate) fn stop(&self) -> Result<(), TransitionErr> {
+        self.command(b'p', [0; 7])
+    }
+
+    /// Saves the pattern memory in the non-volatile memory of the device.
+    pub(crate) fn save_patterns(&self) -> Result<(), TransitionErr> {
+        self.command(b'W', [0xBE, 0xEF, 0xCA, 0xFE, 0, 0, 0])
+    }
+
+    /// Makes the device play lines `start..end` of the pattern memory in a loop when it's
+    /// powered without the host (blink(1) mk3 only, older devices play the whole memory).
+    pub(crate) fn set_startup(&self, start: u8, end: u8) -> Result<(), TransitionErr> {
+        self.command(b'B', [1, start, end, 0, 0, 0, 0])
+    }
+
+    /// Makes the device play lines `start..end` of the pattern memory when it's not tickled
+    /// again within `ticks` (in units of 10 ms).
+    pub(crate) fn tickle(&self, ticks: u16, start: u8, end: u8) -> Result<(), TransitionErr> {
+        let [th, tl] = ticks.to_be_bytes();
+        self.command(b'D', [1, th, tl, 0, start, end, 0])
+    }
+
+    /// Turns off the playback of the pattern when the device is not tickled.
+    pub(crate) fn stop_tickle(&self) -> Result<(), TransitionErr> {
+        self.command(b'D', [0; 7])
+    }
+
+    fn command(&self, command: u8, args: Args) -> Result<(), TransitionErr> {
+        let mut report = [0; REPORT_SIZE];
+        report[0] = REPORT_ID;
+        report[1] = command;
+        report[2..].copy_from_slice(&args);
+        self.transport.write(&report)
+    }
+
+    /// Sends the command and reads the response of the device.
+    fn query(&self, command: u8, args: Args) -> Result<Report, TransitionErr> {
+        self.command(command, args)?;
+        self.transport.read()
+    }
+}
+
+/// Watchdog settings of the device: timeout (in device units) and the first line of the
+/// "crashed" pattern, which takes the rest of the pattern memory.
+#[derive(Debug, Clone, Copy)]
+struct Watchdog {
+    ticks: u16,
+    start: u8,
+}
+
+/// Sends the messages to all connected blink(1) devices.
+#[derive(Debug)]
+pub(crate) struct BlinkBackend {
+    devices: Vec<Blink1>,
+    watchdog: Mutex<Option<Watchdog>>,
+}
+
+impl BlinkBackend {
+    pub(crate) fn new() -> Result<Self, TransitionErr> {
+        let devices = Blink1::open_all()?;
+        if devices.is_empty() {
+            return Err(TransitionErr::DeviceNotFound);
+        }
+        Ok(Self::with_devices(devices))
+    }
+
+    fn with_devices(devices: Vec<Blink1>) -> Self {
+        Self {
+            devices,
+            watchdog: Mutex::new(None),
+        }
+    }
+
+    /// Number of pattern lines which are not taken by the "crashed" pattern.
+    fn free_lines(&self) -> usize {
+        self.watchdog
+            .lock()
+            .expect("poisoned lock")
+            .map_or(MAX_LINES, |watchdog| usize::from(watchdog.start))
+    }
+}
+
+impl Backend for BlinkBackend {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        for device in &self.devices {
+            device.send(msg)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the color from the device, so also colors set by other programs are known.
+    fn current(&self) -> Option<BlinkMsg> {
+        let device = self.devices.first()?;
+        match device.read_rgb() {
+            Ok((r, g, b)) => Some(BlinkMsg::Immediate(Color::Three(r, g, b), None)),
+            Err(e) => {
+                warn!("cannot read current color ({})", e);
+                None
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn play_pattern(&self, pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        if pattern.len() > self.free_lines() {
+            return Err(TransitionErr::PatternTooLong {
+                lines: pattern.len(),
+                max_lines: self.free_lines(),
+            });
+        }
+        for device in &self.devices {
+            for (position, line) in pattern.iter().enumerate() {
+                device.write_line(position as u8, line)?;
+            }
+            device.play(pattern.len() as u8)?;
+        }
+        Ok(true)
+    }
+
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        for device in &self.devices {
+            device.stop()?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn arm_watchdog(
+        &self,
+        timeout: Duration,
+        pattern: &[PatternLine],
+    ) -> Result<bool, TransitionErr> {
+        if pattern.len() > MAX_LINES {
+            return Err(TransitionErr::PatternTooLong {
+                lines: pattern.len(),
+                max_lines: MAX_LINES,
+            });
+        }
+        let watchdog = Watchdog {
+            ticks: ticks(timeout),
+            start: (MAX_LINES - pattern.len()) as u8,
+        };
+        for device in &self.devices {
+            for (offset, line) in pattern.iter().enumerate() {
+                device.write_line(watchdog.start + offset as u8, line)?;
+            }
+        }
+        *self.watchdog.lock().expect("poisoned lock") = Some(watchdog);
+        self.tickle_watchdog()?;
+        Ok(true)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn tickle_watchdog(&self) -> Result<(), TransitionErr> {
+        let Some(watchdog) = *self.watchdog.lock().expect("poisoned lock") else {
+            return Ok(());
+        };
+        for device in &self.devices {
+            device.tickle(watchdog.ticks, watchdog.start, MAX_LINES as u8)?;
+        }
+        Ok(())
+    }
+
+    fn disarm_watchdog(&self) -> Result<(), TransitionErr> {
+        *self.watchdog.lock().expect("poisoned lock") = None;
+        for device in &self.devices {
+            device.stop_tickle()?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts the duration to units of 10 ms, as expected by the device.
+#[allow(clippy::cast_possible_truncation)]
+fn ticks(duration: Duration) -> u16 {
+    (duration.as_millis() / TICK.as_millis()).min(u128::from(u16::MAX)) as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::utils::FakeTransport;
+
+    fn device() -> (Blink1, Arc<FakeTransport>) {
+        let transport = Arc::new(FakeTransport::new("2000ABCD", 204));
+        (Blink1::new(transport.clone()), transport)
+    }
+
+    #[test]
+    fn test_fade_is_decoded() -> Result<(), TransitionErr> {
+        let (blink1, transport) = device();
+
+        blink1.send(BlinkMsg::Fade(
+            Color::Three(1, 2, 3),
+            Duration::from_millis(500),
+            None,
+        ))?;
+
+        assert_eq!(transport.rgb(), (1, 2, 3));
+        assert_eq!(blink1.read_rgb()?, (1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_off_turns_all_leds_off() -> Result<(), TransitionErr> {
+        let (blink1, transport) = device();
+        blink1.send(BlinkMsg::Immediate(Color::Red, None))?;
+
+        blink1.send(BlinkMsg::Off)?;
+
+        assert_eq!(transport.rgb(), (0, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_is_decoded() -> Result<(), TransitionErr> {
+        let (blink1, _) = device();
+
+        assert_eq!(blink1.version()?, 204);
+        assert_eq!(blink1.serial()?, "2000ABCD");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_is_stored_and_played() -> Result<(), TransitionErr> {
+        let (blink1, transport) = device();
+        let line = PatternLine::new(Color::Blue, Duration::from_millis(500));
+
+        blink1.write_line(3, &line)?;
+        blink1.play(4)?;
+
+        assert_eq!(transport.line(3), ((0, 0, 255), 50));
+        assert_eq!(transport.playing(), Some(4));
+        blink1.stop()?;
+        assert_eq!(transport.playing(), None);
+        Ok(())
+    }
+
+    fn backend_with_fakes() -> (BlinkBackend, Vec<Arc<FakeTransport>>) {
+        let transports = vec![
+            Arc::new(FakeTransport::new("2000ABCD", 204)),
+            Arc::new(FakeTransport::new("3000ABCD", 303)),
+        ];
+        let devices = transports
+            .iter()
+            .map(|transport| Blink1::new(transport.clone()))
+            .collect();
+        (BlinkBackend::with_devices(devices), transports)
+    }
+
+    #[test]
+    fn test_message_is_sent_to_all_devices() -> Result<(), TransitionErr> {
+        let (backend, transports) = backend_with_fakes();
+
+        backend.send(BlinkMsg::Immediate(Color::Green, None))?;
+
+        assert!(transports.iter().all(|t| t.rgb() == (0, 255, 0)));
+        assert!(matches!(
+            backend.current(),
+            Some(BlinkMsg::Immediate(Color::Three(0, 255, 0), None))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_crashed_pattern_takes_end_of_memory() -> Result<(), TransitionErr> {
+        let (backend, transports) = backend_with_fakes();
+        let line = PatternLine::new(Color::Red, Duration::from_millis(500));
+
+        backend.arm_watchdog(Duration::from_secs(5), &[line, line])?;
+        let result = backend.play_pattern(&[line; MAX_LINES - 1]);
+
+        assert_eq!(transports[0].tickle(), Some((500, 30, 32)));
+        assert_eq!(transports[0].line(31), ((255, 0, 0), 50));
+        assert!(matches!(result, Err(TransitionErr::PatternTooLong { .. })));
+        backend.disarm_watchdog()?;
+        assert_eq!(transports[0].tickle(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use crate::backend::delegate;
+use crate::backend::Backend;
+use crate::color;
+use crate::error::TransitionErr;
+use crate::msg;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use chrono::Local;
+use chrono::NaiveTime;
+use chrono::Timelike;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time of day when the LED should be less distracting, e.g. at night.
+///
+/// During quiet hours the brightness is multiplied by the level set with
+/// [`brightness`](QuietHours::brightness()) and, optionally, only failures are displayed.
+///
+/// # Example
+/// ```
+/// use transition::QuietHours;
+///
+/// // from 22:00 to 7:00, 10% of brightness, success is not displayed
+/// let quiet_hours = QuietHours::between(22, 7).brightness(0.1).failures_only();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    from: u32,
+    to: u32,
+    brightness: f32,
+    failures_only: bool,
+}
+
+impl QuietHours {
+    /// Creates quiet hours starting at `from` hour (inclusive) and ending at `to` hour
+    /// (exclusive). The range can span midnight. Hours are in local time and should be in
+    /// `0..=24` range. When both hours are the same (e.g. `between(0, 24)`), quiet hours last
+    /// the whole day.
+    ///
+    /// By default the brightness is dropped to 20%.
+    #[must_use]
+    pub fn between(from: u32, to: u32) -> Self {
+        Self {
+            from: from % 24,
+            to: to % 24,
+            brightness: 0.2,
+            failures_only: false,
+        }
+    }
+
+    /// Sets the brightness used during quiet hours (`0.0` - off, `1.0` - full brightness).
+    #[must_use]
+    pub fn brightness(mut self, level: f32) -> Self {
+        self.brightness = level.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Turns the LED off instead of showing the success color during quiet hours.
+    #[must_use]
+    pub fn failures_only(mut self) -> Self {
+        self.failures_only = true;
+        self
+    }
+
+    pub(crate) fn suppresses_success(&self) -> bool {
+        self.failures_only && self.active_now()
+    }
+
+    fn active_now(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        let hour = time.hour();
+        if self.from == self.to {
+            true
+        } else if self.from < self.to {
+            (self.from..self.to).contains(&hour)
+        } else {
+            hour >= self.from || hour < self.to
+        }
+    }
+}
+
+/// Scales every color sent to the wrapped backend according to the brightness settings.
+#[derive(Debug)]
+pub(crate) struct Dimmed {
+    inner: Arc<dyn Backend>,
+    level: f32,
+    quiet_hours: Option<QuietHours>,
+}
+
+impl Dimmed {
+    pub(crate) fn new(
+        inner: Arc<dyn Backend>,
+        level: f32,
+        quiet_hours: Option<QuietHours>,
+    ) -> Self {
+        Self {
+            inner,
+            level,
+            quiet_hours,
+        }
+    }
+
+    fn factor(&self) -> f32 {
+        match self.quiet_hours {
+            Some(quiet_hours) if quiet_hours.active_now() => self.level * quiet_hours.brightness,
+            _ => self.level,
+        }
+    }
+}
+
+impl Backend for Dimmed {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        let factor = self.factor();
+        self.inner
+            .send(msg::map_color(msg, |c| color::scale(c, factor)))
+    }
+
+    fn play_pattern(&self, pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        let factor = self.factor();
+        let pattern: Vec<PatternLine> = pattern
+            .iter()
+            .map(|line| line.map_color(|c| color::scale(c, factor)))
+            .collect();
+        self.inner.play_pattern(&pattern)
+    }
+
+    fn arm_watchdog(
+        &self,
+        timeout: Duration,
+        pattern: &[PatternLine],
+    ) -> Result<bool, TransitionErr> {
+        let factor = self.factor();
+        let pattern: Vec<PatternLine> = pattern
+            .iter()
+            .map(|line| line.map_color(|c| color::scale(c, factor)))
+            .collect();
+        self.inner.arm_watchdog(timeout, &pattern)
+    }
+
+    delegate!(
+        current,
+        stop_pattern,
+        tickle_watchdog,
+        disarm_watchdog,
+        state,
+        flush,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 30, 0).expect("invalid time")
+    }
+
+    #[test]
+    fn test_quiet_hours_within_one_day() {
+        let quiet_hours = QuietHours::between(12, 14);
+
+        assert!(!quiet_hours.contains(at(11)));
+        assert!(quiet_hours.contains(at(12)));
+        assert!(quiet_hours.contains(at(13)));
+        assert!(!quiet_hours.contains(at(14)));
+    }
+
+    #[test]
+    fn test_quiet_hours_spanning_midnight() {
+        let quiet_hours = QuietHours::between(22, 7);
+
+        assert!(!quiet_hours.contains(at(21)));
+        assert!(quiet_hours.contains(at(22)));
+        assert!(quiet_hours.contains(at(0)));
+        assert!(quiet_hours.contains(at(6)));
+        assert!(!quiet_hours.contains(at(7)));
+    }
+
+    #[test]
+    fn test_quiet_hours_all_day() {
+        for quiet_hours in [QuietHours::between(0, 24), QuietHours::between(9, 9)] {
+            assert!((0..24).all(|hour| quiet_hours.contains(at(hour))));
+        }
+    }
+}
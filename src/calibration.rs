@@ -0,0 +1,242 @@
+use crate::backend::delegate;
+use crate::backend::Backend;
+use crate::color;
+use crate::color::Color;
+use crate::error::TransitionErr;
+use crate::msg;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Name of the profile used when there is no profile for the device serial.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Color correction of the device - per-channel gamma and gain.
+///
+/// Each channel is corrected with `255 * gain * (value / 255) ^ gamma`. Gamma around `2.2` makes
+/// mixed colors (like [`Led::Orange`](crate::Led::Orange) or [`Led::Pink`](crate::Led::Pink))
+/// look as expected, gain allows to balance channels which are brighter than others.
+///
+/// # Example
+/// ```
+/// use transition::Calibration;
+///
+/// let calibration = Calibration::default()
+///     .gamma(2.2, 2.2, 2.2)
+///     .gain(1.0, 0.8, 0.9);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Calibration {
+    gamma: [f32; 3],
+    gain: [f32; 3],
+}
+
+impl Calibration {
+    /// Sets gamma of red, green and blue channels.
+    #[must_use]
+    pub fn gamma(mut self, red: f32, green: f32, blue: f32) -> Self {
+        self.gamma = [red, green, blue];
+        self
+    }
+
+    /// Sets gain of red, green and blue channels (`0.0` - channel off, `1.0` - unchanged).
+    #[must_use]
+    pub fn gain(mut self, red: f32, green: f32, blue: f32) -> Self {
+        self.gain = [red, green, blue].map(|gain| gain.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Loads the calibration profile of the device with `serial` from a TOML file.
+    ///
+    /// The file contains one table per device serial. The table named `default` is used when
+    /// there is no table for the serial. If there is none of them, the colors are not corrected.
+    ///
+    /// ```toml
+    /// [default]
+    /// gamma = [2.2, 2.2, 2.2]
+    ///
+    /// [2000ABCD]
+    /// gamma = [2.2, 2.0, 2.4]
+    /// gain = [1.0, 0.8, 0.9]
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr`] when the file cannot be read or it's not a valid profile file,
+    /// also when the selected profile has gamma which is not positive or gain out of `0..=1`.
+    pub fn load<P: AsRef<Path>>(path: P, serial: &str) -> Result<Self, TransitionErr> {
+        let content =
+            fs::read_to_string(path).map_err(|e| TransitionErr::Calibration(e.to_string()))?;
+        Self::parse(&content, serial)
+    }
+
+    fn parse(content: &str, serial: &str) -> Result<Self, TransitionErr> {
+        let mut profiles: HashMap<String, Calibration> =
+            toml::from_str(content).map_err(|e| TransitionErr::Calibration(e.to_string()))?;
+        let calibration = profiles
+            .remove(serial)
+            .or_else(|| profiles.remove(DEFAULT_PROFILE))
+            .unwrap_or_default();
+        calibration.validate()?;
+        Ok(calibration)
+    }
+
+    /// Checks the values loaded from the file, the builder methods don't allow invalid gains.
+    fn validate(&self) -> Result<(), TransitionErr> {
+        if let Some(gamma) = self
+            .gamma
+            .iter()
+            .find(|gamma| !(gamma.is_finite() && **gamma > 0.0))
+        {
+            return Err(TransitionErr::Calibration(format!(
+                "gamma must be a positive number, found {gamma}"
+            )));
+        }
+        if let Some(gain) = self.gain.iter().find(|gain| !(0.0..=1.0).contains(*gain)) {
+            return Err(TransitionErr::Calibration(format!(
+                "gain must be between 0 and 1, found {gain}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, color: Color) -> Color {
+        let (r, g, b) = color::rgb(color);
+        Color::Three(self.channel(0, r), self.channel(1, g), self.channel(2, b))
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn channel(&self, idx: usize, value: u8) -> u8 {
+        let normalized = f32::from(value) / 255.0;
+        let corrected = self.gain[idx] * normalized.powf(self.gamma[idx]);
+        (corrected * 255.0).round().clamp(0.0, 255.0) as u8
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            gamma: [1.0; 3],
+            gain: [1.0; 3],
+        }
+    }
+}
+
+/// Corrects every color sent to the wrapped backend using the calibration profile.
+#[derive(Debug)]
+pub(crate) struct Calibrated {
+    inner: Arc<dyn Backend>,
+    calibration: Calibration,
+}
+
+impl Calibrated {
+    pub(crate) fn new(inner: Arc<dyn Backend>, calibration: Calibration) -> Self {
+        Self { inner, calibration }
+    }
+}
+
+impl Backend for Calibrated {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        self.inner
+            .send(msg::map_color(msg, |c| self.calibration.apply(c)))
+    }
+
+    fn play_pattern(&self, pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        let pattern: Vec<PatternLine> = pattern
+            .iter()
+            .map(|line| line.map_color(|c| self.calibration.apply(c)))
+            .collect();
+        self.inner.play_pattern(&pattern)
+    }
+
+    fn arm_watchdog(
+        &self,
+        timeout: Duration,
+        pattern: &[PatternLine],
+    ) -> Result<bool, TransitionErr> {
+        let pattern: Vec<PatternLine> = pattern
+            .iter()
+            .map(|line| line.map_color(|c| self.calibration.apply(c)))
+            .collect();
+        self.inner.arm_watchdog(timeout, &pattern)
+    }
+
+    delegate!(
+        current,
+        stop_pattern,
+        tickle_watchdog,
+        disarm_watchdog,
+        state,
+        flush,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROFILES: &str = r#"
+        [default]
+        gamma = [2.2, 2.2, 2.2]
+
+        [2000ABCD]
+        gain = [1.0, 0.5, 1.0]
+    "#;
+
+    #[test]
+    fn test_default_calibration_does_not_change_colors() {
+        let calibration = Calibration::default();
+
+        assert_eq!(
+            color::rgb(calibration.apply(Color::Three(255, 165, 0))),
+            (255, 165, 0)
+        );
+    }
+
+    #[test]
+    fn test_gamma_and_gain_are_applied() {
+        let calibration = Calibration::default()
+            .gamma(2.0, 1.0, 1.0)
+            .gain(1.0, 1.0, 0.5);
+
+        let result = color::rgb(calibration.apply(Color::Three(128, 128, 128)));
+
+        assert_eq!(result, (64, 128, 64));
+    }
+
+    #[test]
+    fn test_profile_is_selected_by_serial() -> Result<(), TransitionErr> {
+        let calibration = Calibration::parse(PROFILES, "2000ABCD")?;
+
+        assert_eq!(calibration, Calibration::default().gain(1.0, 0.5, 1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_profile_is_used_for_unknown_serial() -> Result<(), TransitionErr> {
+        let calibration = Calibration::parse(PROFILES, "unknown")?;
+
+        assert_eq!(calibration, Calibration::default().gamma(2.2, 2.2, 2.2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_profile_is_rejected() {
+        for profile in [
+            "[default]\ngamma = [-1.0, 2.2, 2.2]",
+            "[default]\ngamma = [nan, 2.2, 2.2]",
+            "[default]\ngain = [1.0, 1.5, 1.0]",
+        ] {
+            assert!(matches!(
+                Calibration::parse(profile, "unknown"),
+                Err(TransitionErr::Calibration(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::color::Color;
+use crate::msg::BlinkMsg;
+use log::warn;
+use std::time::Duration;
+
+const MORSE_UNIT: Duration = Duration::from_millis(200);
+const COUNT_BLINK: Duration = Duration::from_millis(250);
+const COUNT_DIGIT_GAP: Duration = Duration::from_secs(1);
+const OUTCOME_HOLD: Duration = Duration::from_secs(2);
+const CODE_GAP: Duration = Duration::from_secs(1);
+
+/// Describes how the error code is displayed on the LED.
+///
+/// The code is played in a loop after the failure color - see
+/// [`notify_failure_with_code`](crate::Notifier::notify_failure_with_code()) and
+/// [`failure_code`](crate::Transition::failure_code()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlinkCode {
+    /// Each digit is displayed as a number of blinks (zero as ten blinks). Digits are separated
+    /// with a longer pause. Other characters are skipped.
+    #[default]
+    Count,
+
+    /// Letters and digits are displayed using Morse code.
+    Morse,
+}
+
+impl BlinkCode {
+    /// Frames of one cycle: the outcome color, then the code blinked with this color.
+    pub(crate) fn frames(self, text: &str, color: Color) -> Vec<(BlinkMsg, Duration)> {
+        let mut frames = vec![on(color, OUTCOME_HOLD), off(CODE_GAP)];
+        match self {
+            BlinkCode::Count => count(text, color, &mut frames),
+            BlinkCode::Morse => morse(text, color, &mut frames),
+        }
+        if let Some(last) = frames.last_mut() {
+            last.1 = CODE_GAP;
+        }
+        frames
+    }
+}
+
+fn count(text: &str, color: Color, frames: &mut Vec<(BlinkMsg, Duration)>) {
+    for ch in text.chars() {
+        let Some(digit) = ch.to_digit(10) else {
+            warn!("'{}' cannot be displayed by counting blinks, skipping", ch);
+            continue;
+        };
+        let blinks = if digit == 0 { 10 } else { digit };
+        for _ in 0..blinks {
+            frames.push(on(color, COUNT_BLINK));
+            frames.push(off(COUNT_BLINK));
+        }
+        if let Some(last) = frames.last_mut() {
+            last.1 = COUNT_DIGIT_GAP;
+        }
+    }
+}
+
+fn morse(text: &str, color: Color, frames: &mut Vec<(BlinkMsg, Duration)>) {
+    for ch in text.chars() {
+        if ch == ' ' {
+            if let Some(last) = frames.last_mut() {
+                last.1 = MORSE_UNIT * 7;
+            }
+            continue;
+        }
+        let Some(symbols) = morse_symbols(ch) else {
+            warn!("'{}' cannot be displayed using Morse code, skipping", ch);
+            continue;
+        };
+        for symbol in symbols.chars() {
+            let units = if symbol == '-' { 3 } else { 1 };
+            frames.push(on(color, MORSE_UNIT * units));
+            frames.push(off(MORSE_UNIT));
+        }
+        if let Some(last) = frames.last_mut() {
+            last.1 = MORSE_UNIT * 3;
+        }
+    }
+}
+
+fn on(color: Color, hold: Duration) -> (BlinkMsg, Duration) {
+    (BlinkMsg::Immediate(color, None), hold)
+}
+
+fn off(hold: Duration) -> (BlinkMsg, Duration) {
+    (BlinkMsg::Immediate(Color::Three(0, 0, 0), None), hold)
+}
+
+fn morse_symbols(ch: char) -> Option<&'static str> {
+    let symbols = match ch.to_ascii_uppercase() {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        _ => return None,
+    };
+    Some(symbols)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RED: Color = Color::Three(255, 0, 0);
+
+    fn blinks(frames: &[(BlinkMsg, Duration)]) -> Vec<Duration> {
+        // skips the outcome color at the beginning
+        frames
+            .iter()
+            .skip(2)
+            .filter(|(msg, _)| matches!(msg, BlinkMsg::Immediate(Color::Three(255, 0, 0), _)))
+            .map(|(_, hold)| *hold)
+            .collect()
+    }
+
+    #[test]
+    fn test_count_blinks_each_digit() {
+        let frames = BlinkCode::Count.frames("101", RED);
+
+        assert_eq!(blinks(&frames).len(), 1 + 10 + 1);
+    }
+
+    #[test]
+    fn test_count_separates_digits() {
+        let frames = BlinkCode::Count.frames("21", RED);
+
+        let gaps: Vec<Duration> = frames.iter().skip(2).map(|(_, hold)| *hold).collect();
+        assert_eq!(
+            gaps,
+            vec![
+                COUNT_BLINK,
+                COUNT_BLINK,
+                COUNT_BLINK,
+                COUNT_DIGIT_GAP,
+                COUNT_BLINK,
+                CODE_GAP
+            ]
+        );
+    }
+
+    #[test]
+    fn test_morse_uses_dots_and_dashes() {
+        let frames = BlinkCode::Morse.frames("2", RED);
+
+        assert_eq!(
+            blinks(&frames),
+            vec![
+                MORSE_UNIT,
+                MORSE_UNIT,
+                MORSE_UNIT * 3,
+                MORSE_UNIT * 3,
+                MORSE_UNIT * 3
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_characters_are_skipped() {
+        let frames = BlinkCode::Morse.frames("?", RED);
+
+        assert!(blinks(&frames).is_empty());
+    }
+}
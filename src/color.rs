@@ -1,9 +1,17 @@
-use blinkrs::Color;
 use serde::{Deserialize, Serialize};
 
+/// Color sent to the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Color {
+    Red,
+    Green,
+    Blue,
+    Three(u8, u8, u8),
+}
+
 /// Represents the color of the Led.
 #[allow(missing_docs)]
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Led {
     Red,
@@ -42,3 +50,29 @@ impl From<Led> for Color {
         from(&led_color)
     }
 }
+
+/// Returns red, green and blue components of the color.
+pub(crate) fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Red => (255, 0, 0),
+        Color::Green => (0, 255, 0),
+        Color::Blue => (0, 0, 255),
+        Color::Three(r, g, b) => (r, g, b),
+    }
+}
+
+/// Multiplies every component of the color by `factor` (clamped to `0.0..=1.0`).
+pub(crate) fn scale(color: Color, factor: f32) -> Color {
+    let factor = factor.clamp(0.0, 1.0);
+    let (r, g, b) = rgb(color);
+    Color::Three(
+        scale_component(r, factor),
+        scale_component(g, factor),
+        scale_component(b, factor),
+    )
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn scale_component(component: u8, factor: f32) -> u8 {
+    (f32::from(component) * factor).round() as u8
+}
@@ -0,0 +1,200 @@
+use crate::backend::Backend;
+use crate::color;
+use crate::color::Led;
+use crate::msg;
+use crate::msg::BlinkMsg;
+use crate::task::Task;
+use crossbeam_channel::bounded;
+use crossbeam_channel::RecvTimeoutError;
+use crossbeam_channel::Sender;
+use log::debug;
+use log::warn;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+const DIM_STEPS: i32 = 4;
+const FADE: Duration = Duration::from_secs(1);
+
+/// Describes what happens with the outcome color when it gets old.
+///
+/// See [`stale_after`](crate::Transition::stale_after()).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decay {
+    /// Halves the brightness of the outcome color after each period, four times in total.
+    Dim,
+
+    /// Switches the LED to the specified color.
+    Color(Led),
+
+    /// Turns the LED off.
+    Off,
+}
+
+impl Decay {
+    fn steps(self, outcome: BlinkMsg) -> Vec<BlinkMsg> {
+        match self {
+            Decay::Dim => (1..=DIM_STEPS)
+                .map(|step| msg::map_color(outcome, |c| color::scale(c, 0.5_f32.powi(step))))
+                .collect(),
+            Decay::Color(led) => vec![BlinkMsg::Fade(led.into(), FADE, None)],
+            Decay::Off => vec![BlinkMsg::Off],
+        }
+    }
+}
+
+/// Cancels the outcome animations and decays scheduled so far, when dropped. Outcomes of the
+/// earlier transitions stop changing the colors, as different transitions usually share the same
+/// device.
+static SCHEDULED: Mutex<Vec<Sender<()>>> = Mutex::new(Vec::new());
+
+/// Stops the outcome animations and decays of all transitions finished so far.
+pub(crate) fn supersede() {
+    SCHEDULED.lock().expect("poisoned lock").clear();
+}
+
+/// Loops the outcome `animation` and applies the `decay` in background - the first step after
+/// `period` passes from the outcome, every next one after another `period`. Stops when any
+/// transition starts (see [`supersede`]). An animation which takes no time is played once.
+pub(crate) fn schedule(
+    backend: Arc<dyn Backend>,
+    outcome: BlinkMsg,
+    animation: Option<Arc<dyn Task>>,
+    decay: Option<(Duration, Decay)>,
+) {
+    let (cancel, cancelled) = bounded::<()>(0);
+    SCHEDULED.lock().expect("poisoned lock").push(cancel);
+    // nothing is ever sent, the wait ends early only when the sender is dropped
+    let superseded_within = move |timeout| {
+        matches!(
+            cancelled.recv_timeout(timeout),
+            Err(RecvTimeoutError::Disconnected)
+        )
+    };
+    debug!("scheduling outcome animation and decay: {:?}", decay);
+    thread::spawn(move || {
+        let mut deadline = decay.map(|(period, _)| Instant::now() + period);
+        if let Some(animation) = animation {
+            let looped = animation.frames().iter().any(|(_, hold)| !hold.is_zero());
+            if !looped {
+                warn!("outcome animation takes no time, it's played only once");
+            }
+            loop {
+                if superseded_within(Duration::ZERO) {
+                    debug!("transition started again, outcome animation stopped");
+                    return;
+                }
+                if let Err(e) = animation.execute(backend.as_ref(), &superseded_within) {
+                    warn!("failed to play outcome animation: {}", e);
+                    return;
+                }
+                let expired = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                if !looped || expired {
+                    break;
+                }
+            }
+        }
+        let Some((period, decay)) = decay else {
+            return;
+        };
+        for step in decay.steps(outcome) {
+            let wait = deadline.map_or(Duration::ZERO, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            if superseded_within(wait) {
+                debug!("transition started again, decay cancelled");
+                return;
+            }
+            if let Err(e) = backend.send(step) {
+                warn!("failed to apply decay: {}", e);
+                return;
+            }
+            deadline = deadline.map(|deadline| deadline + period);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::TransitionErr;
+    use crate::task::BlinkTask;
+
+    #[derive(Debug, Default)]
+    struct BackendSpy {
+        messages: Mutex<Vec<BlinkMsg>>,
+    }
+
+    impl Backend for BackendSpy {
+        fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+            self.messages.lock().expect("poisoned lock").push(msg);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_animation_without_duration_is_played_once() {
+        let spy = Arc::new(BackendSpy::default());
+        let instant = BlinkTask::from_frames(vec![(BlinkMsg::Off, Duration::ZERO)]);
+
+        schedule(spy.clone(), BlinkMsg::Off, Some(Arc::new(instant)), None);
+        thread::sleep(Duration::from_millis(100));
+
+        let played = spy.messages.lock().expect("poisoned lock").len();
+        assert!(played <= 1, "Animation was played {played} times");
+    }
+
+    #[test]
+    fn test_animation_stops_when_any_transition_starts() {
+        let spy = Arc::new(BackendSpy::default());
+        let blink = BlinkTask::from_frames(vec![(BlinkMsg::Off, Duration::from_millis(10))]);
+
+        schedule(spy.clone(), BlinkMsg::Off, Some(Arc::new(blink)), None);
+        thread::sleep(Duration::from_millis(50));
+        supersede();
+        thread::sleep(Duration::from_millis(20));
+        let played = spy.messages.lock().expect("poisoned lock").len();
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(spy.messages.lock().expect("poisoned lock").len(), played);
+    }
+
+    #[test]
+    fn test_superseded_decay_ends_without_waiting_for_period() {
+        let spy = Arc::new(BackendSpy::default());
+
+        schedule(
+            spy.clone(),
+            BlinkMsg::Off,
+            None,
+            Some((Duration::from_secs(1800), Decay::Off)),
+        );
+        thread::sleep(Duration::from_millis(20));
+        supersede();
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(Arc::strong_count(&spy), 1, "Decay thread has ended");
+        assert!(spy.messages.lock().expect("poisoned lock").is_empty());
+    }
+
+    #[test]
+    fn test_dim_halves_brightness_in_each_step() {
+        let outcome = BlinkMsg::Fade(Led::White.into(), FADE, None);
+
+        let steps = Decay::Dim.steps(outcome);
+
+        let colors: Vec<(u8, u8, u8)> = steps
+            .into_iter()
+            .map(|step| match step {
+                BlinkMsg::Fade(c, _, _) => color::rgb(c),
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(
+            colors,
+            vec![(128, 128, 128), (64, 64, 64), (32, 32, 32), (16, 16, 16)]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::blink1::Blink1;
+use crate::color::Color;
+use crate::color::Led;
+use crate::effect::Effect;
+use crate::error::TransitionErr;
+use crate::flash::FlashGuard;
+use crate::pattern;
+use crate::pattern::PatternLine;
+use crate::pattern::MAX_LINES;
+use crate::task::BlinkTask;
+use crate::task::Task;
+use log::info;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MK1_PATTERN_LINES: usize = 12;
+
+/// Hardware revision of the blink(1) device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// The first blink(1), with a single LED and 12 pattern lines.
+    Mk1,
+    /// blink(1) mk2, with two LEDs and 32 pattern lines.
+    Mk2,
+    /// blink(1) mk3, with two LEDs and 32 pattern lines.
+    Mk3,
+}
+
+impl Model {
+    /// Number of lines in the pattern memory.
+    fn pattern_lines(self) -> usize {
+        match self {
+            Model::Mk1 => MK1_PATTERN_LINES,
+            Model::Mk2 | Model::Mk3 => MAX_LINES,
+        }
+    }
+
+    fn from_version(version: u16) -> Self {
+        match version {
+            0..=199 => Model::Mk1,
+            200..=299 => Model::Mk2,
+            _ => Model::Mk3,
+        }
+    }
+}
+
+/// Connected blink(1) device. See [`devices`].
+#[derive(Debug)]
+pub struct Device {
+    blink1: Blink1,
+    serial: String,
+    firmware_version: u16,
+}
+
+impl Device {
+    fn new(blink1: Blink1) -> Result<Self, TransitionErr> {
+        Ok(Self {
+            serial: blink1.serial()?,
+            firmware_version: blink1.version()?,
+            blink1,
+        })
+    }
+
+    /// Serial number of the device, e.g. `2000ABCD`. It's also used to pick the
+    /// [`Calibration`](crate::Calibration) profile.
+    #[must_use]
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    /// Firmware version of the device, e.g. `204` for v2.04.
+    #[must_use]
+    pub fn firmware_version(&self) -> u16 {
+        self.firmware_version
+    }
+
+    /// Hardware revision of the device.
+    #[must_use]
+    pub fn model(&self) -> Model {
+        Model::from_version(self.firmware_version)
+    }
+
+    /// Reads red, green and blue components of the color which is currently displayed.
+    ///
+    /// # Errors
+    ///
+    /// If the device was disconnected or cannot be accessed, [`TransitionErr::Usb`] is returned.
+    pub fn current_color(&self) -> Result<(u8, u8, u8), TransitionErr> {
+        self.blink1.read_rgb()
+    }
+
+    /// Saves `colors` as the startup pattern, played in a loop when the device is powered but no
+    /// software controls it (e.g. while the machine boots). Colors are changed in the same way
+    /// as the "pending" colors of [`Transition`](crate::Transition).
+    ///
+    /// The pattern replaces the whole pattern memory of the device.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use transition::Led;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// for device in transition::devices()? {
+    ///     device.save_startup_pattern(&[Led::Orange, Led::Blank])?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the pattern does not fit in the device memory, [`TransitionErr::PatternTooLong`] is
+    /// returned. If the device cannot be accessed, [`TransitionErr::Usb`] is returned.
+    pub fn save_startup_pattern(&self, colors: &[Led]) -> Result<(), TransitionErr> {
+        self.save_startup(Arc::new(BlinkTask::new(colors)))
+    }
+
+    /// Saves the [`Effect`] as the startup pattern. See
+    /// [`save_startup_pattern`](Device::save_startup_pattern()).
+    ///
+    /// # Errors
+    ///
+    /// If the effect does not fit in the device memory, [`TransitionErr::PatternTooLong`] is
+    /// returned. If the device cannot be accessed, [`TransitionErr::Usb`] is returned.
+    pub fn save_startup_effect(&self, effect: &Effect) -> Result<(), TransitionErr> {
+        self.save_startup(Arc::new(BlinkTask::effect(effect)))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_startup(&self, task: Arc<dyn Task>) -> Result<(), TransitionErr> {
+        // the pattern is played when no software is running, so the guard cannot be disabled
+        let task = FlashGuard::default().apply(task)?;
+        let mut lines = pattern::compile(&task.frames())?;
+        let max_lines = self.model().pattern_lines();
+        if lines.len() > max_lines {
+            return Err(TransitionErr::PatternTooLong {
+                lines: lines.len(),
+                max_lines,
+            });
+        }
+        let end = lines.len() as u8;
+        // older devices play the whole memory, so the rest of it is cleared
+        lines.resize(
+            max_lines,
+            PatternLine::new(Color::Three(0, 0, 0), Duration::ZERO),
+        );
+        for (position, line) in lines.iter().enumerate() {
+            self.blink1.write_line(position as u8, line)?;
+        }
+        // startup params are written to flash together with the pattern, so they go first
+        if self.model() == Model::Mk3 {
+            self.blink1.set_startup(0, end)?;
+        }
+        self.blink1.save_patterns()?;
+        info!("startup pattern saved to device {}", self.serial);
+        Ok(())
+    }
+}
+
+/// Lists all connected blink(1) devices.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// for device in transition::devices()? {
+///     println!(
+///         "{} ({:?}, firmware {}) shows {:?}",
+///         device.serial(),
+///         device.model(),
+///         device.firmware_version(),
+///         device.current_color()?
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If USB devices cannot be listed or any blink(1) cannot be accessed (e.g. the user has no
+/// rights to do it), [`TransitionErr::Usb`] is returned.
+pub fn devices() -> Result<Vec<Device>, TransitionErr> {
+    Blink1::open_all()?.into_iter().map(Device::new).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::utils::FakeTransport;
+
+    #[test]
+    fn test_model_is_recognized_by_firmware_version() {
+        assert_eq!(Model::from_version(106), Model::Mk1);
+        assert_eq!(Model::from_version(204), Model::Mk2);
+        assert_eq!(Model::from_version(303), Model::Mk3);
+    }
+
+    #[test]
+    fn test_startup_pattern_is_saved() -> Result<(), TransitionErr> {
+        let transport = Arc::new(FakeTransport::new("3000ABCD", 303));
+        let device = Device::new(Blink1::new(transport.clone()))?;
+
+        device.save_startup_pattern(&[Led::Blue, Led::Blank])?;
+
+        assert_eq!(device.model(), Model::Mk3);
+        assert_eq!(transport.line(0), ((0, 0, 255), 50));
+        assert!(transport.saved(), "Pattern was saved in flash");
+        Ok(())
+    }
+
+    #[test]
+    fn test_startup_params_are_set_before_saving() -> Result<(), TransitionErr> {
+        let transport = Arc::new(FakeTransport::new("3000ABCD", 303));
+        let device = Device::new(Blink1::new(transport.clone()))?;
+
+        device.save_startup_pattern(&[Led::Blue, Led::Blank])?;
+
+        let commands = transport.commands();
+        let position = |command| commands.iter().rposition(|&sent| sent == command);
+        assert!(position(b'B').is_some(), "Startup params were set");
+        assert!(
+            position(b'B') < position(b'W'),
+            "Unexpected order: {commands:?}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::color;
+use crate::color::Color;
+use crate::color::Led;
+use crate::msg::BlinkMsg;
+use std::time::Duration;
+
+const HEARTBEAT_PULSE: Duration = Duration::from_millis(100);
+const HEARTBEAT_GAP: Duration = Duration::from_millis(150);
+const RAINBOW_HUES: u32 = 12;
+const CANDLE_FRAMES: u32 = 40;
+/// Range of strobe frequencies, faster changes are not displayed reliably by the devices.
+const STROBE_FREQUENCIES: (f32, f32) = (0.1, 50.0);
+
+/// Ready to use animations.
+///
+/// Can be used as a "pending" pattern (see
+/// [`pending_effect`](crate::Transition::pending_effect())) or as an outcome (see
+/// [`on_success_effect`](crate::Transition::on_success_effect()) and
+/// [`on_failure_effect`](crate::Transition::on_failure_effect())).
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use transition::{Effect, Led};
+///
+/// let effect = Effect::Breathing {
+///     color: Led::Blue,
+///     period: Duration::from_secs(3),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Slowly brightens and dims the color. One breath takes `period`.
+    Breathing {
+        /// Color of the effect.
+        color: Led,
+        /// Duration of one breath.
+        period: Duration,
+    },
+
+    /// Two quick pulses followed by a pause. One beat takes `period`.
+    Heartbeat {
+        /// Color of the effect.
+        color: Led,
+        /// Duration of one beat, including the pause. The pulses take 500 ms, shorter periods
+        /// are extended to it.
+        period: Duration,
+    },
+
+    /// Cycles through all hues. One cycle takes `period`.
+    Rainbow {
+        /// Duration of one cycle.
+        period: Duration,
+    },
+
+    /// Irregular flicker of the color, like a candle flame.
+    Candle {
+        /// Color of the effect.
+        color: Led,
+    },
+
+    /// Flashes the color on and off `frequency` times per second.
+    Strobe {
+        /// Color of the effect.
+        color: Led,
+        /// Number of flashes per second, limited to 0.1 - 50 (invalid values are treated as
+        /// 0.1).
+        frequency: f32,
+    },
+}
+
+impl Effect {
+    /// Messages of one cycle of the effect, each followed by the time to wait before the next one.
+    pub(crate) fn frames(&self) -> Vec<(BlinkMsg, Duration)> {
+        match *self {
+            Effect::Breathing { color, period } => {
+                let half = period / 2;
+                vec![
+                    fade(color.into(), half),
+                    fade(color::scale(color.into(), 0.05), half),
+                ]
+            }
+            Effect::Heartbeat { color, period } => {
+                let beats = (HEARTBEAT_PULSE + HEARTBEAT_GAP) * 2;
+                let rest = period.max(beats) - beats;
+                vec![
+                    fade(color.into(), HEARTBEAT_PULSE),
+                    fade(color::scale(color.into(), 0.1), HEARTBEAT_GAP),
+                    fade(color.into(), HEARTBEAT_PULSE),
+                    fade(Led::Blank.into(), HEARTBEAT_GAP),
+                    (BlinkMsg::Off, rest),
+                ]
+            }
+            Effect::Rainbow { period } => {
+                let step = period / RAINBOW_HUES;
+                (0..RAINBOW_HUES)
+                    .map(|idx| fade(hue(idx * 360 / RAINBOW_HUES), step))
+                    .collect()
+            }
+            Effect::Candle { color } => {
+                let mut random = XorShift(0x2545_F491);
+                (0..CANDLE_FRAMES)
+                    .map(|_| {
+                        let level = 0.5 + 0.5 * random.next_fraction();
+                        let duration = Duration::from_millis(60 + u64::from(random.next() % 90));
+                        fade(color::scale(color.into(), level), duration)
+                    })
+                    .collect()
+            }
+            Effect::Strobe { color, frequency } => {
+                let (min, max) = STROBE_FREQUENCIES;
+                let frequency = if frequency.is_nan() {
+                    min
+                } else {
+                    frequency.clamp(min, max)
+                };
+                let half = Duration::from_secs_f32(0.5 / frequency);
+                vec![
+                    (BlinkMsg::Immediate(color.into(), None), half),
+                    (BlinkMsg::Immediate(Led::Blank.into(), None), half),
+                ]
+            }
+        }
+    }
+}
+
+fn fade(color: Color, duration: Duration) -> (BlinkMsg, Duration) {
+    (BlinkMsg::Fade(color, duration, None), duration)
+}
+
+/// Fully saturated color with given `degrees` hue.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hue(degrees: u32) -> Color {
+    let sector = degrees / 60;
+    let rising = ((degrees % 60) * 255 / 60) as u8;
+    let falling = 255 - rising;
+    match sector {
+        0 => Color::Three(255, rising, 0),
+        1 => Color::Three(falling, 255, 0),
+        2 => Color::Three(0, 255, rising),
+        3 => Color::Three(0, falling, 255),
+        4 => Color::Three(rising, 0, 255),
+        _ => Color::Three(255, 0, falling),
+    }
+}
+
+/// Simple pseudo-random generator, good enough for the candle flicker.
+struct XorShift(u32);
+
+impl XorShift {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn next_fraction(&mut self) -> f32 {
+        self.next() as f32 / u32::MAX as f32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cycle(effect: Effect) -> Duration {
+        effect.frames().iter().map(|(_, hold)| *hold).sum()
+    }
+
+    #[test]
+    fn test_cycle_takes_period() {
+        let period = Duration::from_secs(2);
+
+        assert_eq!(
+            cycle(Effect::Breathing {
+                color: Led::Blue,
+                period
+            }),
+            period
+        );
+        assert_eq!(
+            cycle(Effect::Heartbeat {
+                color: Led::Red,
+                period
+            }),
+            period
+        );
+        assert_eq!(
+            cycle(Effect::Rainbow {
+                period: Duration::from_millis(1200)
+            }),
+            Duration::from_millis(1200)
+        );
+    }
+
+    #[test]
+    fn test_strobe_flashes_with_frequency() {
+        let effect = Effect::Strobe {
+            color: Led::White,
+            frequency: 2.0,
+        };
+
+        assert_eq!(effect.frames().len(), 2);
+        assert_eq!(cycle(effect), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_strobe_frequency_is_limited() {
+        let strobe = |frequency| Effect::Strobe {
+            color: Led::White,
+            frequency,
+        };
+
+        assert_eq!(cycle(strobe(f32::INFINITY)), Duration::from_millis(20));
+        assert_eq!(cycle(strobe(0.0)), Duration::from_secs(10));
+        assert_eq!(cycle(strobe(f32::NAN)), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_short_heartbeat_is_extended_to_pulses() {
+        let effect = Effect::Heartbeat {
+            color: Led::Red,
+            period: Duration::from_millis(100),
+        };
+
+        assert_eq!(cycle(effect), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_rainbow_starts_with_red() {
+        let frames = Effect::Rainbow {
+            period: Duration::from_secs(1),
+        }
+        .frames();
+
+        assert!(matches!(
+            frames[0].0,
+            BlinkMsg::Fade(Color::Three(255, 0, 0), _, _)
+        ));
+    }
+}
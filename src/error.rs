@@ -1,18 +1,77 @@
 use crate::notifier::MsgType;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Error descriping issue with the transition.
+///
+/// New variants may be added in minor releases (some exist only with the `blink1` feature), so
+/// matches on the error need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum TransitionErr {
-    /// Describes issue with the connection to the blink(1) device.
+    /// Describes missing blink(1) device.
     ///
     /// Make sure that your blink is connected and that your user have correct rights to access
-    /// blink device.
-    #[error("cannot contact blink(1) device")]
-    BlinkConnection(#[from] blinkrs::BlinkError),
+    /// blink device. It's also returned when the crate was built without the `blink1` feature.
+    #[error("blink(1) device not found")]
+    DeviceNotFound,
 
     /// Describes issue with sending a message via a crossbeam_channel to inform blinking thread to
     /// stop execution.
     #[error("cannot notify second thread")]
     Notification(#[from] crossbeam_channel::SendError<MsgType>),
+
+    /// Describes issue with the light other than blink(1), e.g. [`SysfsLed`](crate::SysfsLed).
+    #[error("cannot control the light: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Describes the light which does not exist, e.g. unknown [`OpenRgb`](crate::OpenRgb) device
+    /// or zone.
+    #[error("light not found: {0}")]
+    LightNotFound(String),
+
+    /// Describes the error reported by the [`Plugin`](crate::Plugin) or its invalid response.
+    #[error("plugin failed: {0}")]
+    Plugin(String),
+
+    /// Describes issue with loading of the [`Calibration`](crate::Calibration) profile.
+    #[error("cannot load calibration profile: {0}")]
+    Calibration(String),
+
+    /// Describes the pattern which flashes too fast and was rejected by the
+    /// [`FlashGuard`](crate::FlashGuard).
+    #[error("pattern flashes {rate:.1} times per second, allowed are {max_rate:.1}")]
+    FlashRate {
+        /// Number of flashes per second of the pattern.
+        rate: f32,
+        /// Allowed number of flashes per second.
+        max_rate: f32,
+    },
+
+    /// Describes issue with the connection to the blink(1) device.
+    #[cfg(feature = "blink1")]
+    #[error("cannot access blink(1) device: {0}")]
+    Usb(#[from] rusb::Error),
+
+    /// Describes the pattern which does not fit in the device memory (see
+    /// [`device_pattern`](crate::Transition::device_pattern())).
+    #[error("pattern needs {lines} lines, device can store {max_lines}")]
+    PatternTooLong {
+        /// Number of lines needed by the pattern.
+        lines: usize,
+        /// Number of lines the device can store.
+        max_lines: usize,
+    },
+
+    /// Describes the timeout of the [`crash_watchdog`](crate::Transition::crash_watchdog())
+    /// which the device cannot use.
+    #[error("watchdog timeout {timeout:?} is out of supported range {min:?} - {max:?}")]
+    WatchdogTimeout {
+        /// Requested timeout.
+        timeout: Duration,
+        /// Shortest supported timeout.
+        min: Duration,
+        /// Longest supported timeout.
+        max: Duration,
+    },
 }
@@ -0,0 +1,339 @@
+use crate::backend::Backend;
+use crate::error::TransitionErr;
+use crate::light::State;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use crossbeam_channel::bounded;
+use crossbeam_channel::Sender;
+use log::debug;
+use log::warn;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// What happens when one of the lights added with
+/// [`add_light`](crate::Transition::add_light()) fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// The error is silently skipped.
+    Ignore,
+    /// The error is logged as a warning (using [`log`](https://docs.rs/log) crate) and skipped.
+    /// When the light keeps failing, only the first error is logged.
+    Log,
+    /// The error ends the transition. The light is updated in background, so the error is
+    /// returned by one of the next color changes - at the latest when the transition finishes.
+    Abort,
+}
+
+/// How long the finished transition waits for the added lights to display the outcome.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Commands waiting for the added light.
+#[derive(Debug)]
+enum Command {
+    Send(BlinkMsg),
+    State(State),
+    Flush(Sender<()>),
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    commands: Mutex<VecDeque<Command>>,
+    failure: Mutex<Option<TransitionErr>>,
+}
+
+impl Queue {
+    fn next(&self) -> Option<Command> {
+        self.commands.lock().expect("poisoned lock").pop_front()
+    }
+
+    fn failed(&self, light: &dyn Backend, e: TransitionErr, policy: ErrorPolicy, failing: bool) {
+        match policy {
+            ErrorPolicy::Ignore => {}
+            ErrorPolicy::Log if failing => debug!("light {:?} still fails: {}", light, e),
+            ErrorPolicy::Log => warn!("light {:?} failed: {}", light, e),
+            ErrorPolicy::Abort => {
+                self.failure.lock().expect("poisoned lock").get_or_insert(e);
+            }
+        }
+    }
+}
+
+/// Light added with [`add_light`](crate::Transition::add_light()), updated by its own thread, so
+/// a slow light doesn't hold up the others. Colors which were replaced before the light
+/// displayed them are skipped.
+#[derive(Debug)]
+pub(crate) struct Sink {
+    light: Arc<dyn Backend>,
+    queue: Arc<Queue>,
+    wake: Sender<()>,
+    last: Mutex<Option<BlinkMsg>>,
+}
+
+impl Sink {
+    pub(crate) fn new(light: Arc<dyn Backend>, policy: ErrorPolicy) -> Self {
+        let queue = Arc::new(Queue::default());
+        let (wake, woken) = bounded(1);
+        let (worker, updated) = (queue.clone(), light.clone());
+        thread::spawn(move || {
+            let mut failing = false;
+            while woken.recv().is_ok() {
+                while let Some(command) = worker.next() {
+                    let result = match command {
+                        Command::Send(msg) => updated.send(msg),
+                        Command::State(state) => updated.state(state),
+                        Command::Flush(done) => {
+                            let _ = done.send(());
+                            continue;
+                        }
+                    };
+                    failing = match result {
+                        Ok(()) => false,
+                        Err(e) => {
+                            worker.failed(updated.as_ref(), e, policy, failing);
+                            true
+                        }
+                    };
+                }
+            }
+        });
+        Self {
+            light,
+            queue,
+            wake,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn push(&self, command: Command) {
+        let mut commands = self.queue.commands.lock().expect("poisoned lock");
+        match (command, commands.back_mut()) {
+            (Command::Send(msg), Some(Command::Send(stale))) => *stale = msg,
+            (command, _) => commands.push_back(command),
+        }
+        drop(commands);
+        let _ = self.wake.try_send(()); // full when the worker was already woken
+    }
+
+    /// Returns the error of the earlier command, when the policy is [`ErrorPolicy::Abort`].
+    fn failure(&self) -> Result<(), TransitionErr> {
+        match self.queue.failure.lock().expect("poisoned lock").take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        *self.last.lock().expect("poisoned lock") = Some(msg);
+        self.push(Command::Send(msg));
+        self.failure()
+    }
+
+    /// The last message sent to the light, even if it's not displayed yet.
+    pub(crate) fn current(&self) -> Option<BlinkMsg> {
+        *self.last.lock().expect("poisoned lock")
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.push(Command::State(state));
+        self.failure()
+    }
+
+    fn flush(&self) -> Result<(), TransitionErr> {
+        let (done, flushed) = bounded(1);
+        self.push(Command::Flush(done));
+        if flushed.recv_timeout(FLUSH_TIMEOUT).is_err() {
+            warn!("light {:?} did not catch up in time", self.light);
+        }
+        self.failure()
+    }
+}
+
+/// Forwards every command to the primary backend and the added lights.
+#[derive(Debug)]
+pub(crate) struct FanOut {
+    primary: Arc<dyn Backend>,
+    sinks: Vec<Arc<Sink>>,
+}
+
+impl FanOut {
+    pub(crate) fn new(primary: Arc<dyn Backend>, sinks: Vec<Arc<Sink>>) -> Self {
+        Self { primary, sinks }
+    }
+
+    /// Passes the command to all sinks. Returns the error of the primary backend, or of the
+    /// sink with [`ErrorPolicy::Abort`].
+    fn all<F>(&self, primary: Result<(), TransitionErr>, command: F) -> Result<(), TransitionErr>
+    where
+        F: Fn(&Sink) -> Result<(), TransitionErr>,
+    {
+        self.sinks
+            .iter()
+            .map(|sink| command(sink))
+            .fold(primary, Result::and)
+    }
+}
+
+impl Backend for FanOut {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        self.all(self.primary.send(msg), |sink| sink.send(msg))
+    }
+
+    fn current(&self) -> Option<BlinkMsg> {
+        self.primary.current()
+    }
+
+    /// Patterns are always played by the host, so all the sinks display them.
+    fn play_pattern(&self, _pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        Ok(false)
+    }
+
+    fn arm_watchdog(
+        &self,
+        timeout: Duration,
+        pattern: &[PatternLine],
+    ) -> Result<bool, TransitionErr> {
+        self.primary.arm_watchdog(timeout, pattern)
+    }
+
+    fn tickle_watchdog(&self) -> Result<(), TransitionErr> {
+        self.primary.tickle_watchdog()
+    }
+
+    fn disarm_watchdog(&self) -> Result<(), TransitionErr> {
+        self.primary.disarm_watchdog()
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.all(self.primary.state(state), |sink| sink.state(state))
+    }
+
+    fn flush(&self) -> Result<(), TransitionErr> {
+        self.all(self.primary.flush(), Sink::flush)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+    use std::io;
+    use std::time::Instant;
+
+    #[derive(Debug, Default)]
+    struct BackendSpy {
+        messages: Mutex<Vec<BlinkMsg>>,
+        delay: Duration,
+    }
+
+    impl Backend for BackendSpy {
+        fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+            thread::sleep(self.delay);
+            self.messages.lock().expect("poisoned lock").push(msg);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct Broken;
+
+    impl Backend for Broken {
+        fn send(&self, _msg: BlinkMsg) -> Result<(), TransitionErr> {
+            Err(io::Error::other("bridge unreachable").into())
+        }
+    }
+
+    fn fan_out(policy: ErrorPolicy) -> (FanOut, Arc<BackendSpy>, Arc<BackendSpy>) {
+        let primary = Arc::new(BackendSpy::default());
+        let sink = Arc::new(BackendSpy::default());
+        let sinks = vec![
+            Arc::new(Sink::new(Arc::new(Broken), policy)),
+            Arc::new(Sink::new(sink.clone(), ErrorPolicy::Abort)),
+        ];
+        (FanOut::new(primary.clone(), sinks), primary, sink)
+    }
+
+    #[test]
+    fn test_failing_light_does_not_stop_others() -> Result<(), TransitionErr> {
+        for policy in [ErrorPolicy::Ignore, ErrorPolicy::Log] {
+            let (fan_out, primary, sink) = fan_out(policy);
+
+            fan_out.send(BlinkMsg::Off)?;
+            fan_out.flush()?;
+
+            assert_eq!(
+                *primary.messages.lock().expect("poisoned lock"),
+                vec![BlinkMsg::Off]
+            );
+            assert_eq!(
+                *sink.messages.lock().expect("poisoned lock"),
+                vec![BlinkMsg::Off]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_abort_policy_returns_error_after_sending_to_all() {
+        let (fan_out, primary, sink) = fan_out(ErrorPolicy::Abort);
+
+        let sent = fan_out.send(BlinkMsg::Off);
+        let flushed = fan_out.flush();
+
+        assert!(matches!(sent.and(flushed), Err(TransitionErr::Io(_))));
+        assert_eq!(primary.messages.lock().expect("poisoned lock").len(), 1);
+        assert_eq!(sink.messages.lock().expect("poisoned lock").len(), 1);
+    }
+
+    #[test]
+    fn test_slow_light_does_not_hold_up_others() -> Result<(), TransitionErr> {
+        let primary = Arc::new(BackendSpy::default());
+        let slow = Arc::new(BackendSpy {
+            delay: Duration::from_millis(100),
+            ..BackendSpy::default()
+        });
+        let sink = Arc::new(Sink::new(slow.clone(), ErrorPolicy::Log));
+        let fan_out = FanOut::new(primary.clone(), vec![sink]);
+        let colors: Vec<BlinkMsg> = (0..20)
+            .map(|red| BlinkMsg::Immediate(Color::Three(red, 0, 0), None))
+            .collect();
+
+        let started = Instant::now();
+        for &msg in &colors {
+            fan_out.send(msg)?;
+        }
+        let elapsed = started.elapsed();
+        fan_out.flush()?;
+
+        assert!(elapsed < Duration::from_millis(100), "Took {elapsed:?}");
+        assert_eq!(*primary.messages.lock().expect("poisoned lock"), colors);
+        let displayed = slow.messages.lock().expect("poisoned lock");
+        assert!(displayed.len() < colors.len(), "Stale colors were skipped");
+        assert_eq!(displayed.last(), colors.last());
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct PatternDevice;
+
+    impl Backend for PatternDevice {
+        fn send(&self, _msg: BlinkMsg) -> Result<(), TransitionErr> {
+            Ok(())
+        }
+
+        fn play_pattern(&self, _pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_patterns_are_played_by_host() -> Result<(), TransitionErr> {
+        let sink = Arc::new(Sink::new(Arc::new(BackendSpy::default()), ErrorPolicy::Log));
+        let fan_out = FanOut::new(Arc::new(PatternDevice), vec![sink]);
+
+        assert!(!fan_out.play_pattern(&[])?, "Sinks would miss the pattern");
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use crate::error::TransitionErr;
+use crate::interpolation::to_linear;
+use crate::msg;
+use crate::msg::BlinkMsg;
+use crate::task::BlinkTask;
+use crate::task::Task;
+use log::warn;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Change of relative luminance which is considered a flash (10% of the maximum luminance).
+const LARGE_CHANGE: f32 = 0.1;
+
+/// Period in which the flashes are counted.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Protects from patterns which flash too fast, which may be dangerous for photosensitive people.
+///
+/// Before the transition starts, every pattern (pending colors, effects) is analysed. A flash is
+/// a pair of opposing changes of relative luminance, each of at least 10% of the maximum
+/// luminance. When a pattern flashes more often than allowed, it's slowed down (or rejected - see
+/// [`reject`](FlashGuard::reject())).
+///
+/// The guard is enabled by default with the limit of 3 flashes within any second. See
+/// [`flash_guard`](crate::Transition::flash_guard()) and
+/// [`disable_flash_guard`](crate::Transition::disable_flash_guard()).
+///
+/// # Example
+/// ```
+/// use transition::FlashGuard;
+///
+/// let guard = FlashGuard::new(2.0).reject();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlashGuard {
+    max_rate: f32,
+    reject: bool,
+}
+
+impl FlashGuard {
+    /// Creates the guard which allows at most `max_rate` flashes within any second.
+    #[must_use]
+    pub fn new(max_rate: f32) -> Self {
+        Self {
+            max_rate: max_rate.max(f32::EPSILON),
+            reject: false,
+        }
+    }
+
+    /// Makes [`start`](crate::Transition::start()) fail with [`TransitionErr::FlashRate`] instead
+    /// of slowing the pattern down.
+    #[must_use]
+    pub fn reject(mut self) -> Self {
+        self.reject = true;
+        self
+    }
+
+    /// Returns the task, slowed down if needed.
+    pub(crate) fn apply(&self, task: Arc<dyn Task>) -> Result<Arc<dyn Task>, TransitionErr> {
+        let frames = task.frames();
+        let rate = flash_rate(&frames);
+        if rate <= self.max_rate {
+            return Ok(task);
+        }
+        if self.reject {
+            return Err(TransitionErr::FlashRate {
+                rate,
+                max_rate: self.max_rate,
+            });
+        }
+        warn!(
+            "pattern flashes {:.1} times per second (limit is {:.1}), slowing it down",
+            rate, self.max_rate
+        );
+        // stretching a burst spreads it over more windows, so it may need a few rounds
+        let mut factor = 1.0;
+        let mut stretched_rate = rate;
+        let mut stretched = Vec::new();
+        while stretched_rate > self.max_rate {
+            factor *= stretched_rate / self.max_rate;
+            stretched = frames
+                .iter()
+                .map(|&(msg, hold)| (stretch(msg, factor), hold.mul_f32(factor)))
+                .collect();
+            stretched_rate = flash_rate(&stretched);
+        }
+        Ok(Arc::new(BlinkTask::from_frames(stretched)))
+    }
+}
+
+impl Default for FlashGuard {
+    fn default() -> Self {
+        Self::new(3.0)
+    }
+}
+
+fn stretch(msg: BlinkMsg, factor: f32) -> BlinkMsg {
+    match msg {
+        BlinkMsg::Fade(color, duration, led) => {
+            BlinkMsg::Fade(color, duration.mul_f32(factor), led)
+        }
+        other => other,
+    }
+}
+
+/// Highest number of flashes within any one second when the frames are played in a loop (like
+/// WCAG 2.3.1 "Three Flashes" rule), so a fast burst is not hidden by a long hold.
+pub(crate) fn flash_rate(frames: &[(BlinkMsg, Duration)]) -> f32 {
+    // frames without hold are replaced before they can be seen
+    let frames: Vec<_> = frames.iter().filter(|(_, hold)| !hold.is_zero()).collect();
+    let cycle: Duration = frames.iter().map(|(_, hold)| *hold).sum();
+    if cycle.is_zero() {
+        return 0.0;
+    }
+    let changes: Vec<u128> = changes(&frames).iter().map(Duration::as_nanos).collect();
+    let (cycle, window) = (cycle.as_nanos(), WINDOW.as_nanos());
+    // each window covers all changes of the full cycles, and some of the next, partial one
+    let (full_cycles, rest) = (window / cycle, window % cycle);
+    let changes_per_cycle = changes.len() as u128;
+    let most_changes = changes
+        .iter()
+        .map(|&start| {
+            let partial = changes
+                .iter()
+                .filter(|&&at| (at + cycle - start) % cycle < rest)
+                .count();
+            full_cycles * changes_per_cycle + partial as u128
+        })
+        .max()
+        .unwrap_or(0);
+    most_changes as f32 / 2.0
+}
+
+/// Times (since the start of the loop) of large changes of luminance, in one cycle.
+fn changes(frames: &[&(BlinkMsg, Duration)]) -> Vec<Duration> {
+    let luminances: Vec<f32> = frames.iter().map(|&&(msg, _)| luminance(msg)).collect();
+    // the first pass only finds the starting point, changes are recorded in the second one
+    let mut direction = 0.0;
+    let mut extreme = luminances[luminances.len() - 1];
+    let mut changes = Vec::new();
+    let mut at = Duration::ZERO;
+    for (idx, &current) in luminances
+        .iter()
+        .cycle()
+        .take(luminances.len() * 2)
+        .enumerate()
+    {
+        let delta = current - extreme;
+        if delta * direction > 0.0 {
+            extreme = current;
+        } else if delta.abs() >= LARGE_CHANGE {
+            direction = delta.signum();
+            extreme = current;
+            if idx >= luminances.len() {
+                changes.push(at);
+            }
+        }
+        if idx >= luminances.len() {
+            at += frames[idx - luminances.len()].1;
+        }
+    }
+    changes
+}
+
+/// Relative luminance of the color the message ends with.
+fn luminance(msg: BlinkMsg) -> f32 {
+    let (r, g, b) = msg::rgb(msg);
+    0.2126 * to_linear(r) + 0.7152 * to_linear(g) + 0.0722 * to_linear(b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Led;
+    use crate::effect::Effect;
+    use crate::interpolation::Easing;
+
+    fn strobe(frequency: f32) -> Arc<dyn Task> {
+        Arc::new(BlinkTask::effect(&Effect::Strobe {
+            color: Led::White,
+            frequency,
+        }))
+    }
+
+    #[test]
+    fn test_strobe_flash_rate() {
+        let rate = flash_rate(&strobe(10.0).frames());
+
+        assert!((rate - 10.0).abs() < 0.01, "Unexpected rate: {rate}");
+    }
+
+    #[test]
+    fn test_nanosecond_cycle_is_counted_without_timeline() {
+        let frames = [
+            (
+                BlinkMsg::Immediate(Led::White.into(), None),
+                Duration::from_nanos(5),
+            ),
+            (BlinkMsg::Off, Duration::from_nanos(5)),
+        ];
+
+        let rate = flash_rate(&frames);
+
+        assert!((rate - 1e8).abs() < 1.0, "Unexpected rate: {rate}");
+    }
+
+    #[test]
+    fn test_default_transition_is_not_flashing() {
+        let task = BlinkTask::new(&[Led::Blue, Led::Blank]);
+
+        assert!(flash_rate(&task.frames()) < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_gradual_fades_are_counted() {
+        let white = BlinkTask::new(&[Led::White, Led::Blank]);
+        let task = BlinkTask::interpolated(white.get(), Easing::Linear);
+
+        assert!((flash_rate(&task.frames()) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fast_pattern_is_rejected() {
+        let result = FlashGuard::default().reject().apply(strobe(10.0));
+
+        assert!(matches!(result, Err(TransitionErr::FlashRate { .. })));
+    }
+
+    #[test]
+    fn test_fast_pattern_is_slowed_down() -> Result<(), TransitionErr> {
+        let task = FlashGuard::default().apply(strobe(10.0))?;
+
+        assert!((flash_rate(&task.frames()) - 3.0).abs() < 0.01);
+        Ok(())
+    }
+
+    fn burst_then_hold() -> Arc<dyn Task> {
+        let flash = |led: Led| {
+            (
+                BlinkMsg::Immediate((&led).into(), None),
+                Duration::from_millis(50),
+            )
+        };
+        let mut frames: Vec<_> = (0..5)
+            .flat_map(|_| [flash(Led::White), flash(Led::Blank)])
+            .collect();
+        frames.push((BlinkMsg::Off, Duration::from_secs(5)));
+        Arc::new(BlinkTask::from_frames(frames))
+    }
+
+    #[test]
+    fn test_burst_is_not_averaged_out_by_hold() -> Result<(), TransitionErr> {
+        let rate = flash_rate(&burst_then_hold().frames());
+        let slowed = FlashGuard::default().apply(burst_then_hold())?;
+
+        assert!((rate - 5.0).abs() < 0.01, "Unexpected rate: {rate}");
+        assert!(matches!(
+            FlashGuard::default().reject().apply(burst_then_hold()),
+            Err(TransitionErr::FlashRate { .. })
+        ));
+        assert!(flash_rate(&slowed.frames()) <= 3.0);
+        Ok(())
+    }
+}
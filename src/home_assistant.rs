@@ -0,0 +1,150 @@
+use crate::error::TransitionErr;
+use crate::http::check_status;
+use crate::http::json_string;
+use crate::http::Endpoint;
+use crate::http::RateLimiter;
+use crate::light::Light;
+use std::fmt;
+use std::time::Duration;
+
+/// Home Assistant passes each call to the integration of the light (Zigbee, Wi-Fi bulbs), which
+/// is usually slower than the Hue bridge.
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Light entity of Home Assistant, controlled with `light.turn_on` and `light.turn_off` services
+/// of its REST API.
+///
+/// Black turns the light off. Home Assistant fades the light itself, if the light supports it.
+/// Commands are sent at most 4 times per second - faster patterns are slowed down.
+///
+/// The `token` is a long-lived access token, created in the profile of the Home Assistant user.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{HomeAssistantLight, Led, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let lamp = HomeAssistantLight::new("http://homeassistant.local:8123", "token", "light.office")?;
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], lamp)
+///     .on_failure(&Led::Red)
+///     .start()?;
+/// notifier.notify_failure()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct HomeAssistantLight {
+    server: Endpoint,
+    token: String,
+    entity_id: String,
+    limiter: RateLimiter,
+}
+
+impl HomeAssistantLight {
+    /// Creates the light with given `entity_id` (e.g. `light.office`), controlled by Home
+    /// Assistant at `url` (e.g. `http://homeassistant.local:8123`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr::Io`] when the url is not a valid `http://` url.
+    pub fn new(url: &str, token: &str, entity_id: &str) -> Result<Self, TransitionErr> {
+        Ok(Self {
+            server: Endpoint::parse(url)?,
+            token: token.to_string(),
+            entity_id: entity_id.to_string(),
+            limiter: RateLimiter::new(MIN_INTERVAL),
+        })
+    }
+
+    /// Allows to change the minimal time between two commands.
+    #[must_use]
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.limiter.set_interval(interval);
+        self
+    }
+}
+
+impl Light for HomeAssistantLight {
+    fn set(&self, (r, g, b): (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        self.limiter.wait();
+        let entity_id = json_string(&self.entity_id);
+        let transition = fade.as_secs_f32();
+        let (service, body) = if (r, g, b) == (0, 0, 0) {
+            (
+                "turn_off",
+                format!("{{\"entity_id\":\"{entity_id}\",\"transition\":{transition:.1}}}"),
+            )
+        } else {
+            (
+                "turn_on",
+                format!(
+                    "{{\"entity_id\":\"{entity_id}\",\"rgb_color\":[{r},{g},{b}],\
+                     \"brightness\":{},\"transition\":{transition:.1}}}",
+                    r.max(g).max(b)
+                ),
+            )
+        };
+        let path = format!("/api/services/light/{service}");
+        let authorization = format!("Bearer {}", self.token);
+        let (status, response) =
+            self.server
+                .send("POST", &path, &[("Authorization", &authorization)], &body)?;
+        check_status(status, &response)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HomeAssistantLight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HomeAssistantLight")
+            .field("server", &self.server)
+            .field("entity_id", &self.entity_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::utils::HttpStub;
+
+    #[test]
+    fn test_services_are_called() -> Result<(), TransitionErr> {
+        let server = HttpStub::start(200, "[]");
+        let lamp = HomeAssistantLight::new(server.url(), "secret", "light.office")?;
+
+        lamp.set((255, 128, 0), Duration::from_millis(1500))?;
+        lamp.set((0, 0, 0), Duration::ZERO)?;
+
+        let request = server.request();
+        assert!(request.starts_with("POST /api/services/light/turn_on HTTP/1.1"));
+        assert!(request.contains("Authorization: Bearer secret\r\n"));
+        assert!(request.ends_with(
+            r#"{"entity_id":"light.office","rgb_color":[255,128,0],"brightness":255,"transition":1.5}"#
+        ));
+        let request = server.request();
+        assert!(request.starts_with("POST /api/services/light/turn_off HTTP/1.1"));
+        assert!(request.ends_with(r#"{"entity_id":"light.office","transition":0.0}"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejected_token_is_an_error() -> Result<(), TransitionErr> {
+        let server = HttpStub::start(401, "401: Unauthorized");
+        let lamp = HomeAssistantLight::new(server.url(), "wrong", "light.office")?;
+
+        assert!(matches!(
+            lamp.set((255, 0, 0), Duration::ZERO),
+            Err(TransitionErr::Io(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_hides_token() -> Result<(), TransitionErr> {
+        let lamp = HomeAssistantLight::new("http://localhost:8123", "secret", "light.office")?;
+
+        assert!(!format!("{lamp:?}").contains("secret"));
+        Ok(())
+    }
+}
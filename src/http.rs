@@ -0,0 +1,182 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Server of a REST API, reachable with plain HTTP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+impl Endpoint {
+    /// Parses the base url, e.g. `http://192.168.1.2` or `http://homeassistant.local:8123`.
+    pub(crate) fn parse(url: &str) -> io::Result<Self> {
+        let invalid =
+            |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{msg}: {url}"));
+        let authority = url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("only http:// urls are supported"))?
+            .trim_end_matches('/');
+        if authority.is_empty() || authority.contains('/') {
+            return Err(invalid("url should contain only the host and port"));
+        }
+        match authority.rsplit_once(':') {
+            Some((host, port)) => Ok(Self {
+                host: host.to_string(),
+                port: port.parse().map_err(|_| invalid("invalid port"))?,
+            }),
+            None => Ok(Self {
+                host: authority.to_string(),
+                port: 80,
+            }),
+        }
+    }
+
+    /// Sends the request with JSON `body` and returns the status and the body of the response.
+    pub(crate) fn send(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> io::Result<(u16, String)> {
+        let mut stream = self.connect()?;
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n",
+            self.host,
+            self.port,
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        request.push_str(body);
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let status = response
+            .split(' ')
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response"))?;
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, body)| body.to_string())
+            .unwrap_or_default();
+        Ok((status, body))
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        connect_with_timeout((self.host.as_str(), self.port), TIMEOUT)
+    }
+}
+
+/// Connects to the first reachable of the resolved addresses, waiting at most `timeout` for
+/// each of them (instead of the system timeout, which takes minutes). The same timeout is set
+/// for reading and writing.
+pub(crate) fn connect_with_timeout<A: ToSocketAddrs>(
+    address: A,
+    timeout: Duration,
+) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "host not found");
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Returns the error for the response with status other than 2xx.
+pub(crate) fn check_status(status: u16, body: &str) -> io::Result<()> {
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "HTTP status {status}: {}",
+            body.trim()
+        )))
+    }
+}
+
+/// Escapes the string to be put between quotes in JSON.
+pub(crate) fn json_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Keeps requests at least `interval` apart - the caller waits for its turn, so no color change
+/// is lost, but fast patterns are slowed down.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    last: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub(crate) fn wait(&self) {
+        let mut last = self.last.lock().expect("poisoned lock");
+        if let Some(elapsed) = last.map(|last| last.elapsed()) {
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_parsing() -> io::Result<()> {
+        let endpoint = Endpoint::parse("http://homeassistant.local:8123/")?;
+
+        assert_eq!(endpoint.host, "homeassistant.local");
+        assert_eq!(endpoint.port, 8123);
+        assert_eq!(Endpoint::parse("http://10.0.0.2")?.port, 80);
+        assert!(Endpoint::parse("https://10.0.0.2").is_err());
+        assert!(Endpoint::parse("http://10.0.0.2/api").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_requests_are_rate_limited() {
+        let limiter = RateLimiter::new(Duration::from_millis(50));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.wait();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
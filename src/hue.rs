@@ -0,0 +1,164 @@
+use crate::error::TransitionErr;
+use crate::http::check_status;
+use crate::http::Endpoint;
+use crate::http::RateLimiter;
+use crate::interpolation::to_linear;
+use crate::light::Light;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Philips recommends at most 10 commands per second for one light.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Light connected to the Philips Hue bridge, controlled with its local REST API.
+///
+/// Colors are converted to the CIE xy coordinates and brightness of the bulb, black turns the
+/// light off. The bridge fades the light itself. Commands are sent at most 10 times per second,
+/// as recommended by Philips - faster patterns are slowed down.
+///
+/// The `username` is the application key created by pressing the link button on the bridge and
+/// sending `POST /api` with `{"devicetype":"transition"}`.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{HueLight, Led, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let lamp = HueLight::new("http://192.168.1.2", "app-key", "3")?;
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], lamp)
+///     .on_failure(&Led::Red)
+///     .start()?;
+/// notifier.notify_failure()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct HueLight {
+    bridge: Endpoint,
+    username: String,
+    light: String,
+    limiter: RateLimiter,
+}
+
+impl HueLight {
+    /// Creates the light with given `light` id, controlled by the bridge at `bridge_url` (e.g.
+    /// `http://192.168.1.2`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr::Io`] when the url is not a valid `http://` url.
+    pub fn new(bridge_url: &str, username: &str, light: &str) -> Result<Self, TransitionErr> {
+        Ok(Self {
+            bridge: Endpoint::parse(bridge_url)?,
+            username: username.to_string(),
+            light: light.to_string(),
+            limiter: RateLimiter::new(MIN_INTERVAL),
+        })
+    }
+
+    /// Allows to change the minimal time between two commands.
+    #[must_use]
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.limiter.set_interval(interval);
+        self
+    }
+}
+
+impl Light for HueLight {
+    fn set(&self, rgb: (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        self.limiter.wait();
+        let path = format!("/api/{}/lights/{}/state", self.username, self.light);
+        let (status, body) = self.bridge.send("PUT", &path, &[], &state(rgb, fade))?;
+        check_status(status, &body)?;
+        // the bridge reports errors with 200 status
+        if body.contains("\"error\"") {
+            return Err(io::Error::other(body).into());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HueLight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HueLight")
+            .field("bridge", &self.bridge)
+            .field("light", &self.light)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Body of the light state request.
+fn state(rgb: (u8, u8, u8), fade: Duration) -> String {
+    let transition_time = u16::try_from(fade.as_millis() / 100).unwrap_or(u16::MAX);
+    if rgb == (0, 0, 0) {
+        return format!("{{\"on\":false,\"transitiontime\":{transition_time}}}");
+    }
+    let (x, y) = xy(rgb);
+    format!(
+        "{{\"on\":true,\"xy\":[{x:.4},{y:.4}],\"bri\":{},\"transitiontime\":{transition_time}}}",
+        brightness(rgb)
+    )
+}
+
+/// CIE xy coordinates of the sRGB color.
+fn xy((r, g, b): (u8, u8, u8)) -> (f32, f32) {
+    let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+    let x = r * 0.4124 + g * 0.3576 + b * 0.1805;
+    let y = r * 0.2126 + g * 0.7152 + b * 0.0722;
+    let z = r * 0.0193 + g * 0.1192 + b * 0.9505;
+    let sum = x + y + z;
+    (x / sum, y / sum)
+}
+
+/// Brightness of the bulb (1 - 254) taken from the brightest component of the color.
+fn brightness((r, g, b): (u8, u8, u8)) -> u16 {
+    1 + u16::from(r.max(g).max(b)) * 253 / 255
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::utils::HttpStub;
+
+    #[test]
+    fn test_red_is_converted_to_xy() {
+        let (x, y) = xy((255, 0, 0));
+
+        assert!((x - 0.64).abs() < 0.001);
+        assert!((y - 0.33).abs() < 0.001);
+        assert_eq!(brightness((255, 0, 0)), 254);
+        assert_eq!(brightness((1, 0, 0)), 1);
+    }
+
+    #[test]
+    fn test_color_is_sent_to_bridge() -> Result<(), TransitionErr> {
+        let bridge = HttpStub::start(200, r#"[{"success":{}}]"#);
+        let lamp = HueLight::new(bridge.url(), "key", "3")?;
+
+        lamp.set((255, 0, 0), Duration::from_millis(500))?;
+        lamp.set((0, 0, 0), Duration::ZERO)?;
+
+        let request = bridge.request();
+        assert!(request.starts_with("PUT /api/key/lights/3/state HTTP/1.1"));
+        assert!(
+            request.ends_with(r#"{"on":true,"xy":[0.6401,0.3300],"bri":254,"transitiontime":5}"#)
+        );
+        assert!(bridge
+            .request()
+            .ends_with(r#"{"on":false,"transitiontime":0}"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bridge_error_is_reported() -> Result<(), TransitionErr> {
+        let bridge = HttpStub::start(200, r#"[{"error":{"type":1}}]"#);
+        let lamp = HueLight::new(bridge.url(), "wrong", "3")?;
+
+        assert!(matches!(
+            lamp.set((255, 0, 0), Duration::ZERO),
+            Err(TransitionErr::Io(_))
+        ));
+        Ok(())
+    }
+}
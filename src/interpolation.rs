@@ -0,0 +1,141 @@
+use std::f32::consts::PI;
+
+/// Describes how fast the color changes during the fade.
+///
+/// See [`interpolate`](crate::Transition::interpolate()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed of the change.
+    Linear,
+
+    /// Slow at the beginning and at the end (cubic curve).
+    EaseInOut,
+
+    /// Slow at the beginning and at the end (sine curve), a bit softer than
+    /// [`EaseInOut`](Easing::EaseInOut).
+    Sine,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::Sine => -((PI * t).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+/// Computes `steps` colors between `from` (exclusive) and `to` (inclusive). The interpolation is
+/// done in `OKLab` color space, so intermediate colors don't get muddy.
+pub(crate) fn frames(
+    from: (u8, u8, u8),
+    to: (u8, u8, u8),
+    steps: u16,
+    easing: Easing,
+) -> Vec<(u8, u8, u8)> {
+    let from = to_oklab(from);
+    let to = to_oklab(to);
+    (1..=steps)
+        .map(|step| {
+            let t = easing.apply(f32::from(step) / f32::from(steps));
+            from_oklab([
+                lerp(from[0], to[0], t),
+                lerp(from[1], to[1], t),
+                lerp(from[2], to[2], t),
+            ])
+        })
+        .collect()
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn to_oklab((r, g, b): (u8, u8, u8)) -> [f32; 3] {
+    let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn from_oklab([l, a, b]: [f32; 3]) -> (u8, u8, u8) {
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    (
+        from_linear(4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_),
+        from_linear(-1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_),
+        from_linear(-0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_),
+    )
+}
+
+pub(crate) fn to_linear(component: u8) -> f32 {
+    let c = f32::from(component) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn from_linear(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_easing_keeps_ends() {
+        for easing in [Easing::Linear, Easing::EaseInOut, Easing::Sine] {
+            assert!(
+                easing.apply(0.0).abs() < 1e-6,
+                "{easing:?} does not start at 0"
+            );
+            assert!(
+                (easing.apply(1.0) - 1.0).abs() < 1e-6,
+                "{easing:?} does not end at 1"
+            );
+            assert!(
+                (easing.apply(0.5) - 0.5).abs() < 1e-6,
+                "{easing:?} is not symmetric"
+            );
+        }
+    }
+
+    #[test]
+    fn test_last_frame_is_target_color() {
+        let frames = frames((0, 0, 255), (255, 165, 0), 10, Easing::Linear);
+
+        assert_eq!(frames.len(), 10);
+        assert_eq!(frames.last(), Some(&(255, 165, 0)));
+    }
+
+    #[test]
+    fn test_oklab_roundtrip() {
+        for color in [
+            (255, 0, 0),
+            (0, 255, 0),
+            (0, 0, 255),
+            (255, 192, 203),
+            (0, 0, 0),
+        ] {
+            assert_eq!(from_oklab(to_oklab(color)), color);
+        }
+    }
+}
@@ -36,16 +36,74 @@
 #[cfg(test)]
 mod testutils;
 
+mod backend;
+#[cfg(feature = "blink1")]
+mod blink1;
+mod brightness;
+mod calibration;
+mod code;
 mod color;
+mod decay;
+#[cfg(feature = "blink1")]
+mod device;
+mod effect;
 mod error;
+mod fanout;
+mod flash;
+mod home_assistant;
+mod http;
+mod hue;
+mod interpolation;
+mod light;
+mod logfile;
+mod mqtt;
 mod msg;
 mod notifier;
+mod openrgb;
+mod panic_hook;
+mod pattern;
+mod plugin;
+mod serial;
+mod sysfs;
 mod task;
+mod terminal;
 mod transition;
+#[cfg(feature = "blink1")]
+mod transport;
+mod watchdog;
 
 use doc_comment::doctest;
 
+pub use crate::brightness::QuietHours;
+pub use crate::calibration::Calibration;
+pub use crate::code::BlinkCode;
 pub use crate::color::Led;
+pub use crate::decay::Decay;
+#[cfg(feature = "blink1")]
+pub use crate::device::devices;
+#[cfg(feature = "blink1")]
+pub use crate::device::Device;
+#[cfg(feature = "blink1")]
+pub use crate::device::Model;
+pub use crate::effect::Effect;
+pub use crate::fanout::ErrorPolicy;
+pub use crate::flash::FlashGuard;
+pub use crate::home_assistant::HomeAssistantLight;
+pub use crate::hue::HueLight;
+pub use crate::interpolation::Easing;
+pub use crate::light::Light;
+pub use crate::light::State;
+pub use crate::logfile::LogFile;
+pub use crate::mqtt::MqttPublisher;
+pub use crate::openrgb::OpenRgb;
+pub use crate::panic_hook::install_panic_hook;
+pub use crate::plugin::Plugin;
+pub use crate::serial::SerialLed;
+pub use crate::serial::SerialProtocol;
+pub use crate::sysfs::SysfsLed;
+pub use crate::terminal::ColorDepth;
+pub use crate::terminal::Terminal;
+pub use crate::terminal::TerminalMode;
 pub use crate::transition::Transition;
 pub use error::TransitionErr;
 pub use notifier::Notifier;
@@ -55,6 +113,7 @@ doctest!("../README.md");
 #[cfg(test)]
 mod test {
     use crate::testutils::utils::init_logging;
+    use crate::Led;
     use crate::Transition;
     use crate::TransitionErr;
     use log::debug;
@@ -63,7 +122,7 @@ mod test {
     #[test]
     fn test_clone_of_transition() -> Result<(), TransitionErr> {
         init_logging();
-        let transition = Transition::default();
+        let transition = Transition::with_fallback(&[Led::Blue, Led::Blank]);
         let other_transition = transition.clone();
         let notifier = transition.start()?;
         std::thread::sleep(Duration::from_millis(1000));
@@ -79,14 +138,14 @@ mod test {
     #[test]
     fn test_debug_of_transition() {
         init_logging();
-        let transition = Transition::default();
+        let transition = Transition::with_fallback(&[Led::Blue, Led::Blank]);
         debug!("testing Debug of transition: {:#?}", transition);
     }
 
     #[test]
     fn test_debug_of_notifier() -> Result<(), TransitionErr> {
         init_logging();
-        let notifier = Transition::default().start()?;
+        let notifier = Transition::with_fallback(&[Led::Blue, Led::Blank]).start()?;
         debug!("testing Debug of notifier: {:#?}", notifier);
 
         Ok(())
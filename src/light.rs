@@ -0,0 +1,191 @@
+use crate::backend::Backend;
+use crate::color;
+use crate::error::TransitionErr;
+use crate::msg::BlinkMsg;
+use crate::pattern::PatternLine;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Light which displays the transition instead of the blink(1) device.
+///
+/// Implemented by all alternative backends of this crate (e.g. [`SysfsLed`](crate::SysfsLed)),
+/// and can be implemented to display the transition anywhere else. Pass it to
+/// [`with_light`](crate::Transition::with_light()).
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use transition::{Light, TransitionErr};
+///
+/// #[derive(Debug)]
+/// struct Console;
+///
+/// impl Light for Console {
+///     fn set(&self, (r, g, b): (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+///         println!("color: #{r:02x}{g:02x}{b:02x}");
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Light: Send + Sync + Debug {
+    /// Changes the color to red, green and blue components of `rgb` in `fade` time. Lights which
+    /// cannot fade change the color immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr`] when the color cannot be displayed.
+    fn set(&self, rgb: (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr>;
+
+    /// Informs about the state of the transition - called when it starts and after the outcome
+    /// color is set. Does nothing by default, only lights which can display more than colors
+    /// (e.g. [`MqttPublisher`](crate::MqttPublisher)) need it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr`] when the state cannot be displayed.
+    fn state(&self, _state: State) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+
+    /// Plays the pattern - colors with their fade times - in a loop, without any help from the
+    /// host. Called only when [`device_pattern`](crate::Transition::device_pattern()) is set.
+    /// Returns `false` by default, meaning that the light cannot play patterns and the host
+    /// sends the colors one by one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr`] when the pattern cannot be played.
+    fn play_pattern(&self, _pattern: &[((u8, u8, u8), Duration)]) -> Result<bool, TransitionErr> {
+        Ok(false)
+    }
+
+    /// Stops the pattern started with [`play_pattern`](Light::play_pattern()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransitionErr`] when the pattern cannot be stopped.
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        Ok(())
+    }
+}
+
+/// State of the transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// The transition was started and the task is still running.
+    Pending,
+    /// The task finished with success.
+    Success,
+    /// The task finished with failure.
+    Failure,
+    /// The transition was cancelled and the previous color restored.
+    Cancelled,
+}
+
+impl State {
+    /// Lowercase name of the state, e.g. `pending`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            State::Pending => "pending",
+            State::Success => "success",
+            State::Failure => "failure",
+            State::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Allows to share one light between many transitions.
+impl<L: Light + ?Sized> Light for Arc<L> {
+    fn set(&self, rgb: (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        self.as_ref().set(rgb, fade)
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.as_ref().state(state)
+    }
+
+    fn play_pattern(&self, pattern: &[((u8, u8, u8), Duration)]) -> Result<bool, TransitionErr> {
+        self.as_ref().play_pattern(pattern)
+    }
+
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        self.as_ref().stop_pattern()
+    }
+}
+
+/// Allows to use the [`Light`] as the backend.
+#[derive(Debug)]
+pub(crate) struct LightBackend<L: Light> {
+    light: L,
+}
+
+impl<L: Light> LightBackend<L> {
+    pub(crate) fn new(light: L) -> Self {
+        Self { light }
+    }
+}
+
+impl<L: Light> Backend for LightBackend<L> {
+    fn send(&self, msg: BlinkMsg) -> Result<(), TransitionErr> {
+        match msg {
+            BlinkMsg::Off => self.light.set((0, 0, 0), Duration::ZERO),
+            BlinkMsg::Immediate(color, _) => self.light.set(color::rgb(color), Duration::ZERO),
+            BlinkMsg::Fade(color, fade, _) => self.light.set(color::rgb(color), fade),
+        }
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.light.state(state)
+    }
+
+    fn play_pattern(&self, pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+        let pattern: Vec<_> = pattern
+            .iter()
+            .map(|line| (color::rgb(line.color), line.fade))
+            .collect();
+        self.light.play_pattern(&pattern)
+    }
+
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        self.light.stop_pattern()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+    use std::sync::Mutex;
+
+    type Change = ((u8, u8, u8), Duration);
+
+    #[derive(Debug, Default)]
+    struct LightSpy {
+        colors: Mutex<Vec<Change>>,
+    }
+
+    impl Light for LightSpy {
+        fn set(&self, rgb: (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+            self.colors.lock().expect("poisoned lock").push((rgb, fade));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_messages_are_translated_to_colors() -> Result<(), TransitionErr> {
+        let spy = Arc::new(LightSpy::default());
+        let backend = LightBackend::new(spy.clone());
+        let fade = Duration::from_millis(500);
+
+        backend.send(BlinkMsg::Fade(Color::Blue, fade, None))?;
+        backend.send(BlinkMsg::Off)?;
+
+        assert_eq!(
+            *spy.colors.lock().expect("poisoned lock"),
+            vec![((0, 0, 255), fade), ((0, 0, 0), Duration::ZERO)]
+        );
+        Ok(())
+    }
+}
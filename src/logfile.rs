@@ -0,0 +1,101 @@
+use crate::error::TransitionErr;
+use crate::light::Light;
+use crate::light::State;
+use chrono::Local;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Appends every color change and state of the transition to the text file, one line each, e.g.
+/// `2024-05-06 12:00:00.000 color #0000ff fade 500 ms` or `2024-05-06 12:00:05.000 state success`.
+///
+/// Most useful together with other lights, see [`add_light`](crate::Transition::add_light()).
+/// The file is created if it doesn't exist.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, LogFile, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], LogFile::new("transition.log")).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LogFile {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl LogFile {
+    /// Creates the log which is written to the file at `path`.
+    #[must_use]
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(None),
+        }
+    }
+
+    fn append(&self, line: &str) -> Result<(), TransitionErr> {
+        let mut file = self.file.lock().expect("poisoned lock");
+        if file.is_none() {
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        if let Some(opened) = file.as_mut() {
+            writeln!(opened, "{} {}", Local::now().format(TIME_FORMAT), line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Light for LogFile {
+    fn set(&self, (r, g, b): (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        self.append(&format!(
+            "color #{r:02x}{g:02x}{b:02x} fade {} ms",
+            fade.as_millis()
+        ))
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.append(&format!("state {}", state.name()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn test_changes_are_appended() -> Result<(), TransitionErr> {
+        let path = env::temp_dir().join(format!("transition-log-{}.log", process::id()));
+        let log = LogFile::new(&path);
+
+        log.state(State::Pending)?;
+        log.set((255, 165, 0), Duration::from_millis(250))?;
+
+        let content = fs::read_to_string(&path)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" state pending"));
+        assert!(lines[1].ends_with(" color #ffa500 fade 250 ms"));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+use crate::error::TransitionErr;
+use crate::http::connect_with_timeout;
+use crate::http::json_string;
+use crate::light::Light;
+use crate::light::State;
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEFAULT_CLIENT_ID: &str = "transition";
+const DEFAULT_STATE_TOPIC: &str = "transition/state";
+const DEFAULT_COLOR_TOPIC: &str = "transition/color";
+const DEFAULT_AVAILABILITY_TOPIC: &str = "transition/availability";
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const PROTOCOL_LEVEL: u8 = 4; // MQTT 3.1.1
+const CLEAN_SESSION: u8 = 0x02;
+const RETAINED_WILL: u8 = 0x24; // QoS 0
+const CREDENTIALS: u8 = 0xC0; // user name and password
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH_RETAINED: u8 = 0x31; // QoS 0
+const DISCONNECT: u8 = 0xE0;
+
+/// Publishes the state of the transition and the current color to the MQTT broker, for
+/// dashboards and smart-home systems.
+///
+/// Messages are retained JSON objects, so new subscribers get the last state immediately:
+/// - state topic (`transition/state` by default): `{"state":"pending"}`, where the state is one
+///   of `pending`, `success`, `failure` or `cancelled`,
+/// - color topic (`transition/color` by default):
+///   `{"color":"#ff0000","rgb":[255,0,0],"fade_ms":500}`.
+///
+/// The availability topic (`transition/availability` by default) is `online` while connected.
+/// When the process dies without closing the connection, the broker sets it to `offline` (the
+/// last will), so the dashboards can tell that the state is not updated anymore.
+///
+/// With [`home_assistant_discovery`](MqttPublisher::home_assistant_discovery()) both topics
+/// show up in Home Assistant as sensors. Only plain TCP connections (usually port 1883) are
+/// supported.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, MqttPublisher, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mqtt = MqttPublisher::new("broker.local:1883")
+///     .state_topic("ci/nightly/state")
+///     .color_topic("ci/nightly/color")
+///     .home_assistant_discovery("homeassistant");
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], mqtt).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MqttPublisher {
+    address: String,
+    client_id: String,
+    credentials: Option<(String, String)>,
+    state_topic: String,
+    color_topic: String,
+    availability_topic: String,
+    discovery_prefix: Option<String>,
+    connection: Mutex<Option<TcpStream>>,
+}
+
+impl MqttPublisher {
+    /// Creates the publisher which connects to the broker at `address`, e.g. `localhost:1883`.
+    #[must_use]
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            client_id: DEFAULT_CLIENT_ID.to_string(),
+            credentials: None,
+            state_topic: DEFAULT_STATE_TOPIC.to_string(),
+            color_topic: DEFAULT_COLOR_TOPIC.to_string(),
+            availability_topic: DEFAULT_AVAILABILITY_TOPIC.to_string(),
+            discovery_prefix: None,
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Allows to change the client id (`transition` by default). Each publisher connected to
+    /// the same broker needs a different id.
+    #[must_use]
+    pub fn client_id(mut self, client_id: &str) -> Self {
+        self.client_id = client_id.to_string();
+        self
+    }
+
+    /// Allows to log in to the broker.
+    #[must_use]
+    pub fn credentials(mut self, user: &str, password: &str) -> Self {
+        self.credentials = Some((user.to_string(), password.to_string()));
+        self
+    }
+
+    /// Allows to change the topic of the state messages.
+    #[must_use]
+    pub fn state_topic(mut self, topic: &str) -> Self {
+        self.state_topic = topic.to_string();
+        self
+    }
+
+    /// Allows to change the topic of the color messages.
+    #[must_use]
+    pub fn color_topic(mut self, topic: &str) -> Self {
+        self.color_topic = topic.to_string();
+        self
+    }
+
+    /// Allows to change the topic of the availability messages (`online` or `offline`).
+    #[must_use]
+    pub fn availability_topic(mut self, topic: &str) -> Self {
+        self.availability_topic = topic.to_string();
+        self
+    }
+
+    /// Allows to publish Home Assistant discovery messages (with given discovery `prefix`,
+    /// usually `homeassistant`) when connected, so the state and the color are added as sensors.
+    #[must_use]
+    pub fn home_assistant_discovery(mut self, prefix: &str) -> Self {
+        self.discovery_prefix = Some(prefix.to_string());
+        self
+    }
+
+    fn publish(&self, topic: &str, payload: &str) -> Result<(), TransitionErr> {
+        let mut connection = self.connection.lock().expect("poisoned lock");
+        if connection.is_none() {
+            *connection = Some(self.connect()?);
+        }
+        if let Some(stream) = connection.as_mut() {
+            if let Err(e) = publish(stream, topic, payload) {
+                *connection = None;
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut stream = connect_with_timeout(self.address.as_str(), TIMEOUT)?;
+        // the broker publishes the will when the connection is lost without DISCONNECT
+        let mut flags = CLEAN_SESSION | RETAINED_WILL;
+        let mut payload = string(&self.client_id);
+        payload.extend(string(&self.availability_topic));
+        payload.extend(string(OFFLINE));
+        if let Some((user, password)) = &self.credentials {
+            flags |= CREDENTIALS;
+            payload.extend(string(user));
+            payload.extend(string(password));
+        }
+        // keep alive is turned off, so the idle connection does not need pings
+        let body = [string("MQTT"), vec![PROTOCOL_LEVEL, flags, 0, 0], payload].concat();
+        write_packet(&mut stream, CONNECT, &body)?;
+        let (header, body) = read_packet(&mut stream)?;
+        if header != CONNACK || body.len() != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected response of MQTT broker",
+            ));
+        }
+        if body[1] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("MQTT broker refused connection, return code: {}", body[1]),
+            ));
+        }
+        publish(&mut stream, &self.availability_topic, ONLINE)?;
+        for (topic, config) in self.discovery() {
+            publish(&mut stream, &topic, &config)?;
+        }
+        Ok(stream)
+    }
+
+    /// Home Assistant discovery topics and configs of the state and color sensors.
+    fn discovery(&self) -> Vec<(String, String)> {
+        let Some(prefix) = &self.discovery_prefix else {
+            return Vec::new();
+        };
+        let client_id = json_string(&self.client_id);
+        let availability_topic = json_string(&self.availability_topic);
+        [
+            ("state", &self.state_topic, "mdi:traffic-light"),
+            ("color", &self.color_topic, "mdi:palette"),
+        ]
+        .into_iter()
+        .map(|(field, topic, icon)| {
+            let topic_name = format!("{}/sensor/{}/{}/config", prefix, self.client_id, field);
+            let config = format!(
+                "{{\"name\":\"Transition {field}\",\"unique_id\":\"{client_id}_{field}\",\
+                 \"state_topic\":\"{}\",\"value_template\":\"{{{{ value_json.{field} }}}}\",\
+                 \"availability_topic\":\"{availability_topic}\",\"icon\":\"{icon}\"}}",
+                json_string(topic)
+            );
+            (topic_name, config)
+        })
+        .collect()
+    }
+}
+
+impl Light for MqttPublisher {
+    fn set(&self, (r, g, b): (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        let payload = format!(
+            "{{\"color\":\"#{r:02x}{g:02x}{b:02x}\",\"rgb\":[{r},{g},{b}],\"fade_ms\":{}}}",
+            fade.as_millis()
+        );
+        self.publish(&self.color_topic, &payload)
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        let payload = format!("{{\"state\":\"{}\"}}", state.name());
+        self.publish(&self.state_topic, &payload)
+    }
+}
+
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        if let Ok(Some(stream)) = self.connection.get_mut() {
+            // the will is not published after DISCONNECT
+            let _ = publish(stream, &self.availability_topic, OFFLINE);
+            let _ = write_packet(stream, DISCONNECT, &[]);
+        }
+    }
+}
+
+impl fmt::Debug for MqttPublisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MqttPublisher")
+            .field("address", &self.address)
+            .field("client_id", &self.client_id)
+            .field("state_topic", &self.state_topic)
+            .field("color_topic", &self.color_topic)
+            .field("availability_topic", &self.availability_topic)
+            .field("discovery_prefix", &self.discovery_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+/// UTF-8 string prefixed with its length.
+fn string(value: &str) -> Vec<u8> {
+    let len = u16::try_from(value.len()).unwrap_or(u16::MAX);
+    [&len.to_be_bytes(), &value.as_bytes()[..usize::from(len)]].concat()
+}
+
+fn publish<W: Write>(stream: &mut W, topic: &str, payload: &str) -> io::Result<()> {
+    let body = [string(topic), payload.as_bytes().to_vec()].concat();
+    write_packet(stream, PUBLISH_RETAINED, &body)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_packet<W: Write>(stream: &mut W, header: u8, body: &[u8]) -> io::Result<()> {
+    let mut packet = vec![header];
+    let mut remaining = body.len();
+    loop {
+        let mut byte = (remaining % 128) as u8;
+        remaining /= 128;
+        if remaining > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if remaining == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    stream.write_all(&packet)?;
+    stream.flush()
+}
+
+/// Reads one packet - the first byte of the fixed header and the rest of the packet.
+fn read_packet<R: Read>(stream: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut byte = [0; 1];
+    stream.read_exact(&mut byte)?;
+    let header = byte[0];
+    let mut len = 0;
+    for shift in (0..28).step_by(7) {
+        stream.read_exact(&mut byte)?;
+        len |= usize::from(byte[0] & 0x7F) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body)?;
+    Ok((header, body))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossbeam_channel::Receiver;
+    use std::net::TcpListener;
+    use std::thread;
+
+    type Packet = (u8, Vec<u8>);
+
+    /// Mock broker which accepts one client with given CONNACK `return_code` and passes all
+    /// received packets to the receiver.
+    fn broker(return_code: u8) -> (String, Receiver<Packet>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("cannot start mock broker");
+        let address = listener.local_addr().expect("no address").to_string();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("no connection");
+            let connect = read_packet(&mut stream).expect("no CONNECT");
+            sender.send(connect).expect("cannot pass packet");
+            write_packet(&mut stream, CONNACK, &[0, return_code]).expect("cannot respond");
+            while let Ok(packet) = read_packet(&mut stream) {
+                sender.send(packet).expect("cannot pass packet");
+            }
+        });
+        (address, receiver)
+    }
+
+    fn packet(receiver: &Receiver<Packet>) -> Packet {
+        receiver.recv_timeout(TIMEOUT).expect("missing packet")
+    }
+
+    /// Topic and payload of the retained PUBLISH packet.
+    fn published(receiver: &Receiver<Packet>) -> (String, String) {
+        let (header, body) = packet(receiver);
+        assert_eq!(header, PUBLISH_RETAINED);
+        let len = usize::from(u16::from_be_bytes([body[0], body[1]]));
+        let topic = String::from_utf8_lossy(&body[2..2 + len]).to_string();
+        let payload = String::from_utf8_lossy(&body[2 + len..]).to_string();
+        (topic, payload)
+    }
+
+    #[test]
+    fn test_state_and_color_are_published() -> Result<(), TransitionErr> {
+        let (address, receiver) = broker(0);
+        let mqtt = MqttPublisher::new(&address)
+            .client_id("ci")
+            .credentials("user", "secret")
+            .state_topic("ci/state");
+
+        mqtt.state(State::Pending)?;
+        mqtt.set((255, 165, 0), Duration::from_millis(500))?;
+        mqtt.state(State::Failure)?;
+
+        let (header, connect) = packet(&receiver);
+        assert_eq!(header, CONNECT);
+        assert_eq!(connect[..10], [0, 4, b'M', b'Q', b'T', b'T', 4, 0xE6, 0, 0]);
+        assert_eq!(connect[10..14], [0, 2, b'c', b'i']);
+        assert_eq!(
+            connect[14..39],
+            [&[0, 23][..], b"transition/availability"].concat()
+        );
+        assert_eq!(connect[39..48], [&[0, 7][..], b"offline"].concat());
+        assert_eq!(connect[48..54], [&[0, 4][..], b"user"].concat());
+        assert_eq!(
+            published(&receiver),
+            ("transition/availability".to_string(), "online".to_string())
+        );
+        assert_eq!(
+            published(&receiver),
+            ("ci/state".to_string(), r#"{"state":"pending"}"#.to_string())
+        );
+        assert_eq!(
+            published(&receiver),
+            (
+                "transition/color".to_string(),
+                r##"{"color":"#ffa500","rgb":[255,165,0],"fade_ms":500}"##.to_string()
+            )
+        );
+        assert_eq!(
+            published(&receiver),
+            ("ci/state".to_string(), r#"{"state":"failure"}"#.to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_home_assistant_discovery_is_published_when_connected() -> Result<(), TransitionErr> {
+        let (address, receiver) = broker(0);
+        let mqtt = MqttPublisher::new(&address).home_assistant_discovery("homeassistant");
+
+        mqtt.state(State::Success)?;
+
+        packet(&receiver); // CONNECT
+        published(&receiver); // availability
+        let (topic, config) = published(&receiver);
+        assert_eq!(topic, "homeassistant/sensor/transition/state/config");
+        assert_eq!(
+            config,
+            r#"{"name":"Transition state","unique_id":"transition_state","state_topic":"transition/state","value_template":"{{ value_json.state }}","availability_topic":"transition/availability","icon":"mdi:traffic-light"}"#
+        );
+        assert_eq!(
+            published(&receiver).0,
+            "homeassistant/sensor/transition/color/config"
+        );
+        assert_eq!(published(&receiver).0, "transition/state");
+        Ok(())
+    }
+
+    #[test]
+    fn test_offline_is_published_when_dropped() -> Result<(), TransitionErr> {
+        let (address, receiver) = broker(0);
+        let mqtt = MqttPublisher::new(&address).availability_topic("ci/availability");
+
+        mqtt.state(State::Success)?;
+        drop(mqtt);
+
+        packet(&receiver); // CONNECT
+        assert_eq!(published(&receiver).1, "online");
+        published(&receiver); // state
+        assert_eq!(
+            published(&receiver),
+            ("ci/availability".to_string(), "offline".to_string())
+        );
+        assert_eq!(packet(&receiver).0, DISCONNECT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_refused_connection_is_an_error() {
+        let (address, _receiver) = broker(5); // not authorized
+        let mqtt = MqttPublisher::new(&address);
+
+        assert!(matches!(
+            mqtt.state(State::Pending),
+            Err(TransitionErr::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_long_packets_have_multibyte_length() -> io::Result<()> {
+        let mut packet = Vec::new();
+        write_packet(&mut packet, PUBLISH_RETAINED, &[7; 200])?;
+
+        assert_eq!(packet[..3], [PUBLISH_RETAINED, 0xC8, 0x01]);
+        assert_eq!(
+            read_packet(&mut packet.as_slice())?,
+            (PUBLISH_RETAINED, vec![7; 200])
+        );
+        Ok(())
+    }
+}
@@ -1,14 +1,34 @@
+use crate::backend::Backend;
+use crate::color;
+use crate::color::Color;
 use crate::color::Led;
+use crate::effect::Effect;
 use crate::error::TransitionErr;
-use blinkrs::Blinkers;
-use blinkrs::Message as BlinkMsg;
+use crate::task::BlinkTask;
+use crate::task::Task;
 use core::fmt::Debug;
-use std::clone::Clone;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Message understood by the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlinkMsg {
+    /// Turns the LED off.
+    Off,
+    /// Fades to the color in given time, on the LED with given index (all LEDs when `None`).
+    Fade(Color, Duration, Option<u8>),
+    /// Sets the color immediately, on the LED with given index (all LEDs when `None`).
+    Immediate(Color, Option<u8>),
+}
+
 pub(crate) trait Message: Send + Sync {
-    fn send(&self) -> Result<(), TransitionErr>;
+    fn send(&self, backend: &dyn Backend) -> Result<(), TransitionErr>;
     fn get(&self) -> BlinkMsg;
+
+    /// Animation played in background after the message is sent.
+    fn animation(&self) -> Option<Arc<dyn Task>> {
+        None
+    }
 }
 
 impl Debug for dyn Message {
@@ -17,25 +37,22 @@ impl Debug for dyn Message {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct ColorMessage {
-    blinkers: Blinkers,
     color_msg: BlinkMsg,
 }
 
 impl ColorMessage {
     pub(crate) fn new(color: &Led) -> Self {
-        let blinkers: Blinkers = Blinkers::new().expect("could not find device");
         Self {
             color_msg: color_msg(color),
-            blinkers,
         }
     }
 }
 
 impl Message for ColorMessage {
-    fn send(&self) -> Result<(), TransitionErr> {
-        self.blinkers.send(self.color_msg)?;
+    fn send(&self, backend: &dyn Backend) -> Result<(), TransitionErr> {
+        backend.send(self.color_msg)?;
         Ok(())
     }
 
@@ -44,20 +61,63 @@ impl Message for ColorMessage {
     }
 }
 
-impl Clone for ColorMessage {
-    fn clone(&self) -> Self {
-        let blinkers: Blinkers = Blinkers::new().expect("could not find device");
+/// Displays an [`Effect`] as the outcome. The first frame is sent immediately, then the effect
+/// is looped in background.
+#[derive(Debug, Clone)]
+pub(crate) struct EffectMessage {
+    first_msg: BlinkMsg,
+    effect: Arc<BlinkTask>,
+}
+
+impl EffectMessage {
+    pub(crate) fn new(effect: &Effect) -> Self {
+        let first_msg = effect
+            .frames()
+            .first()
+            .map_or(BlinkMsg::Off, |&(msg, _)| msg);
         Self {
-            blinkers,
-            color_msg: self.color_msg,
+            first_msg,
+            effect: Arc::new(BlinkTask::effect(effect)),
         }
     }
 }
 
+impl Message for EffectMessage {
+    fn send(&self, backend: &dyn Backend) -> Result<(), TransitionErr> {
+        backend.send(self.first_msg)?;
+        Ok(())
+    }
+
+    fn get(&self) -> BlinkMsg {
+        self.first_msg
+    }
+
+    fn animation(&self) -> Option<Arc<dyn Task>> {
+        Some(self.effect.clone())
+    }
+}
+
 fn color_msg(color: &Led) -> BlinkMsg {
     BlinkMsg::Fade(color.into(), Duration::from_millis(500), None)
 }
 
+/// Applies `f` to the color carried by the message. [`BlinkMsg::Off`] is left untouched.
+pub(crate) fn map_color(msg: BlinkMsg, f: impl Fn(Color) -> Color) -> BlinkMsg {
+    match msg {
+        BlinkMsg::Fade(color, duration, led) => BlinkMsg::Fade(f(color), duration, led),
+        BlinkMsg::Immediate(color, led) => BlinkMsg::Immediate(f(color), led),
+        BlinkMsg::Off => BlinkMsg::Off,
+    }
+}
+
+/// Returns red, green and blue components of the color the message ends with.
+pub(crate) fn rgb(msg: BlinkMsg) -> (u8, u8, u8) {
+    match msg {
+        BlinkMsg::Fade(c, _, _) | BlinkMsg::Immediate(c, _) => color::rgb(c),
+        BlinkMsg::Off => (0, 0, 0),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
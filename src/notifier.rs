@@ -102,6 +102,86 @@ impl Notifier {
         self.handle.join().expect("cannot joing thread")?;
         Ok(())
     }
+
+    /// Finishes the transition with failure and displays the error `code`.
+    ///
+    /// Works like [`notify_failure`](Notifier::notify_failure()), but after the failure color,
+    /// the `code` is blinked with the same color. Failure color and the code are displayed in a
+    /// loop until the transition is started again. By default, each digit is displayed as a number
+    /// of blinks - see [`failure_code`](crate::Transition::failure_code()) to use Morse code.
+    ///
+    /// # Example
+    /// ```
+    /// use transition::{Transition, Notifier, Led};
+    /// # use std::{error::Error, time::Duration, thread};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let notifier: Notifier = Transition::new(&[Led::Blue, Led::Blank])?.start()?;
+    /// // blinks using color blue
+    /// thread::sleep(Duration::from_secs(1));
+    /// notifier.notify_failure_with_code(101);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method sends message to blinking thread using crossbeam channel.
+    /// If any error related with sending this message will occur, then this method returns
+    /// [`TransitionErr`].
+    pub fn notify_failure_with_code(self, code: u32) -> Result<(), TransitionErr> {
+        self.notify_failure_with_text(&code.to_string())
+    }
+
+    /// Finishes the transition with failure and displays a short `text` (letters and digits).
+    ///
+    /// Letters can be displayed only with [`BlinkCode::Morse`](crate::BlinkCode::Morse), see
+    /// [`notify_failure_with_code`](Notifier::notify_failure_with_code()) for details.
+    ///
+    /// # Errors
+    ///
+    /// This method sends message to blinking thread using crossbeam channel.
+    /// If any error related with sending this message will occur, then this method returns
+    /// [`TransitionErr`].
+    pub fn notify_failure_with_text(self, text: &str) -> Result<(), TransitionErr> {
+        debug!("notifying about failure with code: {}", text);
+        self.sender
+            .send(MsgType::FailureWithCode(text.to_string()))?;
+        self.handle.join().expect("cannot joing thread")?;
+        Ok(())
+    }
+
+    /// Finishes the transition without any outcome.
+    ///
+    /// Stops the thread which is responsible for blinking of the LED and restores the color
+    /// which was displayed before [`start`](crate::Transition::start()) was called. If the
+    /// previous color is not known, the LED is turned off.
+    ///
+    /// # Example
+    /// ```
+    /// use transition::{Transition, Notifier, Led};
+    /// # use std::{error::Error, time::Duration, thread};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let notifier: Notifier = Transition::new(&[Led::Blue, Led::Blank])?.start()?;
+    /// // blinks using color blue
+    /// thread::sleep(Duration::from_secs(1));
+    /// notifier.cancel();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method sends message to blinking thread using crossbeam channel.
+    /// If any error related with sending this message will occur, then this method returns
+    /// [`TransitionErr`].
+    pub fn cancel(self) -> Result<(), TransitionErr> {
+        debug!("cancelling transition");
+        self.sender.send(MsgType::Cancel)?;
+        self.handle.join().expect("cannot joing thread")?;
+        Ok(())
+    }
 }
 
 /// Messages interchanged between main thread and the thread which is responsible for blinking the
@@ -115,4 +195,11 @@ pub enum MsgType {
     /// Send when [notify_failure](transition::Transition::notify_failure()) is
     /// called.
     Failure,
+
+    /// Send when [notify_failure_with_code](transition::Notifier::notify_failure_with_code())
+    /// or [notify_failure_with_text](transition::Notifier::notify_failure_with_text()) is called.
+    FailureWithCode(String),
+
+    /// Send when [cancel](transition::Notifier::cancel()) is called.
+    Cancel,
 }
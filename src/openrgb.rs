@@ -0,0 +1,429 @@
+use crate::error::TransitionErr;
+use crate::http::connect_with_timeout;
+use crate::light::Light;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:6742";
+const CLIENT_NAME: &[u8] = b"transition\0";
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+const MAGIC: &[u8; 4] = b"ORGB";
+const HEADER_SIZE: usize = 16;
+
+const REQUEST_CONTROLLER_COUNT: u32 = 0;
+const REQUEST_CONTROLLER_DATA: u32 = 1;
+const SET_CLIENT_NAME: u32 = 50;
+const UPDATE_LEDS: u32 = 1050;
+const UPDATE_ZONE_LEDS: u32 = 1051;
+const SET_CUSTOM_MODE: u32 = 1100;
+
+/// Device (or one of its zones) controlled by [OpenRGB](https://openrgb.org) - keyboards, case
+/// fans, RAM sticks and anything else OpenRGB supports.
+///
+/// Talks to the SDK server of OpenRGB (enable it in the "SDK Server" tab or start OpenRGB with
+/// `--server`). All LEDs of the device or zone display the same color. The device is switched to
+/// the "Direct" mode with the first color. OpenRGB cannot fade - use
+/// [`interpolate`](crate::Transition::interpolate()) for smooth changes of "pending" colors.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, OpenRgb, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let keyboard = OpenRgb::new("Corsair K70 RGB MK.2").zone("Keyboard");
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], keyboard).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OpenRgb {
+    address: String,
+    device: String,
+    zone: Option<String>,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl OpenRgb {
+    /// Creates the light for OpenRGB device with given `name` (as displayed by OpenRGB, case
+    /// insensitive), using the server at `127.0.0.1:6742`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            address: DEFAULT_ADDRESS.to_string(),
+            device: name.to_string(),
+            zone: None,
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Allows to use the server at different `address`, e.g. `192.168.1.10:6742`.
+    #[must_use]
+    pub fn address(mut self, address: &str) -> Self {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Allows to light only the zone with given `name` instead of the whole device.
+    #[must_use]
+    pub fn zone(mut self, name: &str) -> Self {
+        self.zone = Some(name.to_string());
+        self
+    }
+
+    fn connect(&self) -> Result<Connection, TransitionErr> {
+        let mut stream = connect_with_timeout(self.address.as_str(), TIMEOUT)?;
+        send(&mut stream, 0, SET_CLIENT_NAME, CLIENT_NAME)?;
+        send(&mut stream, 0, REQUEST_CONTROLLER_COUNT, &[])?;
+        let count = Reader::new(&receive(&mut stream, REQUEST_CONTROLLER_COUNT)?).u32()?;
+        for device in 0..count {
+            send(&mut stream, device, REQUEST_CONTROLLER_DATA, &[])?;
+            let controller = Controller::parse(&receive(&mut stream, REQUEST_CONTROLLER_DATA)?)?;
+            if !controller.name.eq_ignore_ascii_case(&self.device) {
+                continue;
+            }
+            let (zone, leds) = match &self.zone {
+                None => (None, controller.leds),
+                Some(name) => {
+                    let (idx, zone) = (0..)
+                        .zip(&controller.zones)
+                        .find(|(_, zone)| zone.name.eq_ignore_ascii_case(name))
+                        .ok_or_else(|| {
+                            TransitionErr::LightNotFound(format!(
+                                "OpenRGB zone {} of {}",
+                                name, self.device
+                            ))
+                        })?;
+                    (Some(idx), zone.leds)
+                }
+            };
+            send(&mut stream, device, SET_CUSTOM_MODE, &[])?;
+            return Ok(Connection {
+                stream,
+                device,
+                zone,
+                leds,
+            });
+        }
+        Err(TransitionErr::LightNotFound(format!(
+            "OpenRGB device {}",
+            self.device
+        )))
+    }
+}
+
+impl Light for OpenRgb {
+    fn set(&self, rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+        let mut connection = self.connection.lock().expect("poisoned lock");
+        if connection.is_none() {
+            *connection = Some(self.connect()?);
+        }
+        if let Some(opened) = connection.as_mut() {
+            if let Err(e) = opened.update(rgb) {
+                *connection = None;
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Connection to the SDK server with the device (and zone) found.
+#[derive(Debug)]
+struct Connection {
+    stream: TcpStream,
+    device: u32,
+    zone: Option<u32>,
+    leds: u32,
+}
+
+impl Connection {
+    /// Sets all LEDs of the device or zone to the same color.
+    fn update(&mut self, (r, g, b): (u8, u8, u8)) -> io::Result<()> {
+        let leds = u16::try_from(self.leds).unwrap_or(u16::MAX);
+        let mut colors = leds.to_le_bytes().to_vec();
+        for _ in 0..leds {
+            colors.extend_from_slice(&[r, g, b, 0]);
+        }
+        let (id, mut data) = match self.zone {
+            None => (UPDATE_LEDS, Vec::new()),
+            Some(zone) => (UPDATE_ZONE_LEDS, zone.to_le_bytes().to_vec()),
+        };
+        data.extend_from_slice(&colors);
+        let size = u32::try_from(data.len() + 4).unwrap_or(u32::MAX);
+        let packet = [size.to_le_bytes().as_slice(), &data].concat();
+        send(&mut self.stream, self.device, id, &packet)
+    }
+}
+
+/// Part of the controller description needed to light the device.
+#[derive(Debug)]
+struct Controller {
+    name: String,
+    zones: Vec<Zone>,
+    leds: u32,
+}
+
+#[derive(Debug)]
+struct Zone {
+    name: String,
+    leds: u32,
+}
+
+impl Controller {
+    /// Parses the controller data in the format of protocol version 0.
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader::new(data);
+        reader.skip(4 + 4)?; // data size, type
+        let name = reader.string()?;
+        for _ in 0..4 {
+            reader.string()?; // description, version, serial, location
+        }
+        let modes = reader.u16()?;
+        reader.skip(4)?; // active mode
+        for _ in 0..modes {
+            reader.string()?;
+            // value, flags, min and max speed, min and max colors, speed, direction, color mode
+            reader.skip(9 * 4)?;
+            let colors = reader.u16()?;
+            reader.skip(usize::from(colors) * 4)?;
+        }
+        let zones_count = reader.u16()?;
+        let mut zones = Vec::new();
+        for _ in 0..zones_count {
+            let name = reader.string()?;
+            reader.skip(3 * 4)?; // type, min and max LEDs
+            let leds = reader.u32()?;
+            let matrix_size = reader.u16()?;
+            reader.skip(usize::from(matrix_size))?;
+            zones.push(Zone { name, leds });
+        }
+        let leds = u32::from(reader.u16()?);
+        Ok(Self { name, zones, leds })
+    }
+}
+
+/// Reads little-endian values from the packet data.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated OpenRGB packet",
+            ));
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn skip(&mut self, len: usize) -> io::Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads the string prefixed with its length, including the null terminator.
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.u16()?;
+        let bytes = self.take(usize::from(len))?;
+        Ok(String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .to_string())
+    }
+}
+
+fn send<W: Write>(stream: &mut W, device: u32, id: u32, data: &[u8]) -> io::Result<()> {
+    let size = u32::try_from(data.len()).unwrap_or(u32::MAX);
+    let mut packet = Vec::with_capacity(HEADER_SIZE + data.len());
+    packet.extend_from_slice(MAGIC);
+    packet.extend_from_slice(&device.to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&size.to_le_bytes());
+    packet.extend_from_slice(data);
+    stream.write_all(&packet)
+}
+
+/// Reads one packet - the device index, the packet id and the data.
+fn read_packet<R: Read>(stream: &mut R) -> io::Result<(u32, u32, Vec<u8>)> {
+    let mut header = [0; HEADER_SIZE];
+    stream.read_exact(&mut header)?;
+    let mut reader = Reader::new(&header);
+    if reader.take(4)? != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an OpenRGB packet",
+        ));
+    }
+    let device = reader.u32()?;
+    let id = reader.u32()?;
+    let size = reader.u32()?;
+    let mut data = vec![0; size as usize];
+    stream.read_exact(&mut data)?;
+    Ok((device, id, data))
+}
+
+/// Reads the response to the request `id`, skipping notifications sent by the server.
+fn receive<R: Read>(stream: &mut R, id: u32) -> io::Result<Vec<u8>> {
+    loop {
+        let (_, packet_id, data) = read_packet(stream)?;
+        if packet_id == id {
+            return Ok(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossbeam_channel::Receiver;
+    use std::net::TcpListener;
+    use std::thread;
+
+    type Packet = (u32, u32, Vec<u8>);
+
+    fn string(value: &str) -> Vec<u8> {
+        let len = u16::try_from(value.len() + 1).expect("string too long");
+        [&len.to_le_bytes(), value.as_bytes(), &[0]].concat()
+    }
+
+    /// Controller data in the format of protocol version 0, with one mode with one color.
+    fn controller(name: &str, zones: &[(&str, u32)]) -> Vec<u8> {
+        let mut data = vec![0; 4 + 4];
+        data.extend(string(name));
+        for field in ["description", "version", "serial", "location"] {
+            data.extend(string(field));
+        }
+        data.extend(1u16.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(string("Direct"));
+        data.extend([0; 9 * 4]);
+        data.extend(1u16.to_le_bytes());
+        data.extend([255, 0, 0, 0]);
+        data.extend(
+            u16::try_from(zones.len())
+                .expect("too many zones")
+                .to_le_bytes(),
+        );
+        for (name, leds) in zones {
+            data.extend(string(name));
+            data.extend([0; 3 * 4]);
+            data.extend(leds.to_le_bytes());
+            data.extend(12u16.to_le_bytes());
+            data.extend([1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        let leds: u32 = zones.iter().map(|(_, leds)| leds).sum();
+        data.extend(u16::try_from(leds).expect("too many LEDs").to_le_bytes());
+        data
+    }
+
+    /// Mock OpenRGB server with given controllers, passing all commands to the receiver.
+    fn server(controllers: Vec<Vec<u8>>) -> (String, Receiver<Packet>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("cannot start mock server");
+        let address = listener.local_addr().expect("no address").to_string();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("no connection");
+            while let Ok((device, id, data)) = read_packet(&mut stream) {
+                let count = u32::try_from(controllers.len()).expect("too many controllers");
+                match id {
+                    REQUEST_CONTROLLER_COUNT => {
+                        send(&mut stream, 0, id, &count.to_le_bytes()).expect("cannot respond");
+                    }
+                    REQUEST_CONTROLLER_DATA => {
+                        let data = &controllers[device as usize];
+                        send(&mut stream, device, id, data).expect("cannot respond");
+                    }
+                    _ => sender
+                        .send((device, id, data))
+                        .expect("cannot pass command"),
+                }
+            }
+        });
+        (address, receiver)
+    }
+
+    fn commands(receiver: &Receiver<Packet>, count: usize) -> Vec<Packet> {
+        (0..count)
+            .map(|_| receiver.recv_timeout(TIMEOUT).expect("missing command"))
+            .collect()
+    }
+
+    #[test]
+    fn test_zone_of_device_is_lit() -> Result<(), TransitionErr> {
+        let (address, receiver) = server(vec![
+            controller("Mouse", &[("Logo", 1)]),
+            controller("Keyboard", &[("Main", 100), ("Logo", 2)]),
+        ]);
+        let light = OpenRgb::new("keyboard").zone("logo").address(&address);
+
+        light.set((255, 165, 0), Duration::ZERO)?;
+
+        let commands = commands(&receiver, 3);
+        assert_eq!(commands[0], (0, SET_CLIENT_NAME, CLIENT_NAME.to_vec()));
+        assert_eq!(commands[1], (1, SET_CUSTOM_MODE, vec![]));
+        assert_eq!(
+            commands[2],
+            (
+                1,
+                UPDATE_ZONE_LEDS,
+                vec![18, 0, 0, 0, 1, 0, 0, 0, 2, 0, 255, 165, 0, 0, 255, 165, 0, 0]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_whole_device_is_lit() -> Result<(), TransitionErr> {
+        let (address, receiver) = server(vec![controller("Fan", &[("Ring", 2), ("Hub", 1)])]);
+        let light = OpenRgb::new("Fan").address(&address);
+
+        light.set((1, 2, 3), Duration::ZERO)?;
+        light.set((0, 0, 0), Duration::ZERO)?;
+
+        let commands = commands(&receiver, 4);
+        assert_eq!(
+            commands[2],
+            (
+                0,
+                UPDATE_LEDS,
+                vec![18, 0, 0, 0, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]
+            )
+        );
+        assert_eq!(commands[3].1, UPDATE_LEDS, "Connection was reused");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_device_is_an_error() {
+        let (address, _receiver) = server(vec![controller("Fan", &[("Ring", 2)])]);
+        let light = OpenRgb::new("Keyboard").address(&address);
+
+        assert!(matches!(
+            light.set((1, 2, 3), Duration::ZERO),
+            Err(TransitionErr::LightNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::color::Led;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Select;
+use crossbeam_channel::Sender;
+use std::panic;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How long the panicking thread waits for the transitions to display the failure.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+static REGISTRY: Registry = Registry::new();
+
+/// Installs the panic hook which finishes all pending transitions of the process with failure,
+/// like [`notify_failure`](crate::Notifier::notify_failure()) - also when the panic happened in
+/// a thread which doesn't own the [`Notifier`](crate::Notifier).
+///
+/// When `color` is set, it's displayed instead of the failure color, so a panic can be told
+/// apart from a failed task. The previous hook (e.g. the one printing the panic message) is
+/// called first. Then the panicking thread waits up to a second for the transitions to change
+/// the color, so the LED is updated even when the process exits right after the panic.
+///
+/// Notifiers of the transitions finished by the hook return an error, as the transition is
+/// already over.
+///
+/// # Example
+/// ```
+/// use transition::{install_panic_hook, Led};
+///
+/// install_panic_hook(Some(Led::Pink));
+/// ```
+pub fn install_panic_hook(color: Option<Led>) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        REGISTRY.fail_all(color);
+    }));
+}
+
+/// Registers the started transition, so it's informed about panics until the returned watch
+/// is dropped.
+pub(crate) fn watch() -> Watch {
+    REGISTRY.watch()
+}
+
+/// Pending transitions of the process.
+pub(crate) struct Registry {
+    live: Mutex<Vec<Live>>,
+    next_id: AtomicUsize,
+}
+
+struct Live {
+    id: usize,
+    panics: Sender<Option<Led>>,
+    done: Receiver<()>,
+}
+
+impl Registry {
+    pub(crate) const fn new() -> Self {
+        Self {
+            live: Mutex::new(Vec::new()),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// The lock is used during panics, so it must not be lost because of another panic.
+    fn live(&self) -> MutexGuard<'_, Vec<Live>> {
+        self.live.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn watch(&'static self) -> Watch {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (panics_sender, panics) = crossbeam_channel::unbounded();
+        let (done, done_receiver) = crossbeam_channel::bounded(0);
+        self.live().push(Live {
+            id,
+            panics: panics_sender,
+            done: done_receiver,
+        });
+        Watch {
+            registry: self,
+            id,
+            panics,
+            _done: done,
+        }
+    }
+
+    /// Informs all pending transitions about the panic and waits until they finish.
+    fn fail_all(&self, color: Option<Led>) {
+        let live: Vec<_> = self
+            .live()
+            .iter()
+            .map(|live| (live.panics.clone(), live.done.clone()))
+            .collect();
+        for (panics, _) in &live {
+            let _ = panics.send(color);
+        }
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        for (_, done) in live {
+            let _ = done.recv_deadline(deadline); // disconnected when the watch is dropped
+        }
+    }
+}
+
+/// Receives panics while the transition is pending.
+pub(crate) struct Watch {
+    registry: &'static Registry,
+    id: usize,
+    panics: Receiver<Option<Led>>,
+    _done: Sender<()>,
+}
+
+impl Watch {
+    /// Returns the color requested by the panic hook, if there was a panic.
+    pub(crate) fn panicked(&self) -> Option<Option<Led>> {
+        self.panics.try_recv().ok()
+    }
+
+    pub(crate) fn panics(&self) -> &Receiver<Option<Led>> {
+        &self.panics
+    }
+
+    /// Sleeps for `timeout`, returns `true` early when there was a panic. The panic is left in
+    /// the channel for [`panicked`](Watch::panicked()).
+    pub(crate) fn wait(&self, timeout: Duration) -> bool {
+        let mut select = Select::new();
+        select.recv(&self.panics);
+        select.ready_timeout(timeout).is_ok()
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.registry.live().retain(|live| live.id != self.id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    static TEST_REGISTRY: Registry = Registry::new();
+
+    #[test]
+    fn test_panic_waits_until_transitions_fail() {
+        let watch = TEST_REGISTRY.watch();
+        let failed = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let failed = failed.clone();
+            thread::spawn(move || {
+                let color = watch.panics().recv().expect("no panic");
+                thread::sleep(Duration::from_millis(100)); // slow light
+                failed.store(color == Some(Led::Pink), Ordering::SeqCst);
+            })
+        };
+
+        TEST_REGISTRY.fail_all(Some(Led::Pink));
+
+        assert!(
+            failed.load(Ordering::SeqCst),
+            "Transition failed before hook returned"
+        );
+        assert!(
+            TEST_REGISTRY.live().is_empty(),
+            "Finished transition was removed"
+        );
+        handle.join().expect("transition thread failed");
+    }
+}
@@ -0,0 +1,115 @@
+use crate::color;
+use crate::color::Color;
+use crate::error::TransitionErr;
+use crate::msg::BlinkMsg;
+use std::time::Duration;
+
+/// Number of pattern lines in the blink(1) mk2 (and newer) memory.
+pub(crate) const MAX_LINES: usize = 32;
+
+/// Smallest time the device can measure.
+pub(crate) const TICK: Duration = Duration::from_millis(10);
+
+/// One line of the pattern stored in the device: the color is faded to in `fade` and the next
+/// line starts right after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PatternLine {
+    pub(crate) color: Color,
+    pub(crate) fade: Duration,
+}
+
+impl PatternLine {
+    pub(crate) fn new(color: Color, fade: Duration) -> Self {
+        Self { color, fade }
+    }
+
+    /// Applies `f` to the color of the line.
+    pub(crate) fn map_color(self, f: impl Fn(Color) -> Color) -> Self {
+        Self::new(f(self.color), self.fade)
+    }
+}
+
+/// Translates frames of a task (messages with the time to wait after each of them) into lines of
+/// the device pattern.
+pub(crate) fn compile(frames: &[(BlinkMsg, Duration)]) -> Result<Vec<PatternLine>, TransitionErr> {
+    let mut lines = Vec::new();
+    for &(msg, hold) in frames {
+        let (fade, color) = match msg {
+            BlinkMsg::Fade(color, duration, _) => (duration.min(hold), color),
+            BlinkMsg::Immediate(color, _) => (TICK.min(hold), color),
+            BlinkMsg::Off => (TICK.min(hold), Color::Three(0, 0, 0)),
+        };
+        let color = normalized(color);
+        lines.push(PatternLine::new(color, fade));
+        let rest = hold.saturating_sub(fade);
+        if rest >= TICK {
+            lines.push(PatternLine::new(color, rest));
+        }
+    }
+    if lines.len() > MAX_LINES {
+        return Err(TransitionErr::PatternTooLong {
+            lines: lines.len(),
+            max_lines: MAX_LINES,
+        });
+    }
+    Ok(lines)
+}
+
+fn normalized(color: Color) -> Color {
+    let (r, g, b) = color::rgb(color);
+    Color::Three(r, g, b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Led;
+    use crate::interpolation::Easing;
+    use crate::task::BlinkTask;
+    use crate::task::Task;
+
+    const RED: Color = Color::Three(255, 0, 0);
+
+    #[test]
+    fn test_fade_is_held_until_next_frame() -> Result<(), TransitionErr> {
+        let second = Duration::from_secs(1);
+        let lines = compile(&[(BlinkMsg::Fade(RED, second / 2, None), second)])?;
+
+        assert_eq!(
+            lines,
+            vec![
+                PatternLine::new(RED, second / 2),
+                PatternLine::new(RED, second / 2)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_immediate_color_is_set_in_one_tick() -> Result<(), TransitionErr> {
+        let lines = compile(&[(BlinkMsg::Immediate(RED, None), Duration::from_millis(250))])?;
+
+        assert_eq!(lines[0], PatternLine::new(RED, TICK));
+        assert_eq!(lines[1], PatternLine::new(RED, Duration::from_millis(240)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_pending_colors_fit_in_device() -> Result<(), TransitionErr> {
+        let task = BlinkTask::new(&[Led::Blue, Led::Blank]);
+
+        assert_eq!(compile(&task.frames())?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_too_long_pattern_is_rejected() {
+        let task = BlinkTask::new(&[Led::Blue, Led::Blank]);
+        let task = BlinkTask::interpolated(task.get(), Easing::Linear);
+
+        assert!(matches!(
+            compile(&task.frames()),
+            Err(TransitionErr::PatternTooLong { .. })
+        ));
+    }
+}
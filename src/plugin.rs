@@ -0,0 +1,354 @@
+use crate::error::TransitionErr;
+use crate::light::Light;
+use crate::light::State;
+use crossbeam_channel::Receiver;
+use serde::Deserialize;
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+const PROGRAM_PREFIX: &str = "transition-backend-";
+const PROTOCOL_VERSION: u32 = 1;
+const TIMEOUT: Duration = Duration::from_secs(5);
+const EXIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Light implemented by an external program, so new backends can be written in any language.
+///
+/// [`new`](Plugin::new()) runs `transition-backend-<name>` found in `PATH` (like git credential
+/// helpers). The program is started with the first request. It's restarted when it stops
+/// working (e.g. doesn't answer in time), but not when it reports an error.
+///
+/// # Protocol
+///
+/// Requests are JSON objects written to the standard input of the program, one per line. The
+/// program answers each of them with one line on the standard output: `{"ok":true}` or
+/// `{"ok":false,"error":"lamp is offline"}`. The standard error is passed through. The program
+/// should exit when its standard input is closed.
+///
+/// - `{"command":"hello","protocol":1}` - sent first, with the version of the protocol,
+/// - `{"command":"color","rgb":[255,165,0],"hex":"#ffa500","fade_ms":500}` - changes the color,
+/// - `{"command":"state","state":"pending"}` - the state of the transition, one of `pending`,
+///   `success`, `failure` or `cancelled`,
+/// - `{"command":"pattern","lines":[{"rgb":[0,0,255],"fade_ms":500},{"rgb":[0,0,0],"fade_ms":500}]}`
+///   \- sent only with [`device_pattern`](crate::Transition::device_pattern()), asks the program
+///   to play the pattern in a loop on its own. The program answers `{"ok":true,"playing":true}`
+///   when it does, otherwise the colors are sent one by one,
+/// - `{"command":"stop"}` - stops the pattern.
+///
+/// Unknown fields of the responses are ignored, so newer versions of the protocol can add them.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, Plugin, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// // runs `transition-backend-wled` from PATH
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], Plugin::new("wled")).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Plugin {
+    program: OsString,
+    args: Vec<OsString>,
+    process: Mutex<Option<Process>>,
+}
+
+impl Plugin {
+    /// Creates the plugin which runs `transition-backend-<name>` from `PATH`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self::command(format!("{PROGRAM_PREFIX}{name}"))
+    }
+
+    /// Creates the plugin which runs the `program` - the name looked up in `PATH` or the path to
+    /// the executable.
+    #[must_use]
+    pub fn command<S: AsRef<OsStr>>(program: S) -> Self {
+        Self {
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            process: Mutex::new(None),
+        }
+    }
+
+    /// Allows to pass the argument to the program, e.g. the address of the lamp.
+    #[must_use]
+    pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    fn request(&self, request: &Request) -> Result<Response, TransitionErr> {
+        let mut process = self.process.lock().expect("poisoned lock");
+        let running = match process.as_mut() {
+            Some(running) => running,
+            None => process.insert(Process::spawn(&self.program, &self.args)?),
+        };
+        match running.request(request) {
+            Ok(response) => accepted(response),
+            Err(e) => {
+                *process = None; // broken, restarted with the next request
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Light for Plugin {
+    fn set(&self, (r, g, b): (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        self.request(&Request::Color {
+            rgb: [r, g, b],
+            hex: format!("#{r:02x}{g:02x}{b:02x}"),
+            fade_ms: fade.as_millis(),
+        })?;
+        Ok(())
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.request(&Request::State {
+            state: state.name(),
+        })?;
+        Ok(())
+    }
+
+    fn play_pattern(&self, pattern: &[((u8, u8, u8), Duration)]) -> Result<bool, TransitionErr> {
+        let lines = pattern
+            .iter()
+            .map(|&((r, g, b), fade)| Line {
+                rgb: [r, g, b],
+                fade_ms: fade.as_millis(),
+            })
+            .collect();
+        Ok(self.request(&Request::Pattern { lines })?.playing)
+    }
+
+    fn stop_pattern(&self) -> Result<(), TransitionErr> {
+        self.request(&Request::Stop)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+enum Request {
+    Hello {
+        protocol: u32,
+    },
+    Color {
+        rgb: [u8; 3],
+        hex: String,
+        fade_ms: u128,
+    },
+    State {
+        state: &'static str,
+    },
+    Pattern {
+        lines: Vec<Line>,
+    },
+    Stop,
+}
+
+#[derive(Debug, Serialize)]
+struct Line {
+    rgb: [u8; 3],
+    fade_ms: u128,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    ok: bool,
+    error: Option<String>,
+    #[serde(default)]
+    playing: bool,
+}
+
+/// Running plugin program.
+#[derive(Debug)]
+struct Process {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    responses: Receiver<String>,
+}
+
+impl Process {
+    fn spawn(program: &OsStr, args: &[OsString]) -> Result<Self, TransitionErr> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, responses) = crossbeam_channel::unbounded();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut process = Self {
+            stdin: child.stdin.take(),
+            child,
+            responses,
+        };
+        accepted(process.request(&Request::Hello {
+            protocol: PROTOCOL_VERSION,
+        })?)?;
+        Ok(process)
+    }
+
+    /// Sends the request and returns the response. Fails only when the program doesn't work - it
+    /// doesn't read the request or doesn't answer it with a valid response.
+    fn request(&mut self, request: &Request) -> Result<Response, TransitionErr> {
+        let request = serde_json::to_string(request)
+            .map_err(|e| TransitionErr::Plugin(format!("cannot encode request: {e}")))?;
+        let stdin = self.stdin.as_mut().expect("stdin is open until drop");
+        writeln!(stdin, "{request}")?;
+        stdin.flush()?;
+        let response = self
+            .responses
+            .recv_timeout(TIMEOUT)
+            .map_err(|_| TransitionErr::Plugin("no response from the plugin".to_string()))?;
+        serde_json::from_str(&response)
+            .map_err(|e| TransitionErr::Plugin(format!("invalid response {response}: {e}")))
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        drop(self.stdin.take()); // asks the plugin to exit
+        let exited = self
+            .responses
+            .recv_timeout(EXIT_TIMEOUT)
+            .is_err_and(|e| e.is_disconnected());
+        if !exited {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns the response, or the error reported by the program.
+fn accepted(response: Response) -> Result<Response, TransitionErr> {
+    if response.ok {
+        return Ok(response);
+    }
+    let error = response.error.as_deref().unwrap_or("request refused");
+    Err(TransitionErr::Plugin(error.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_responses_are_parsed() {
+        let response: Response =
+            serde_json::from_str(r#" { "ok" : true, "lines": [1, {"a": "}"}], "playing": true } "#)
+                .expect("valid response");
+
+        assert!(response.ok && response.playing);
+        assert!(serde_json::from_str::<Response>(r#"{"ok":}"#).is_err());
+        assert!(serde_json::from_str::<Response>(r#"{"ok":"true"}"#).is_err());
+        assert!(accepted(Response {
+            ok: false,
+            error: None,
+            playing: false
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_plugin_program_name() {
+        assert_eq!(
+            Plugin::new("wled").program,
+            OsString::from("transition-backend-wled")
+        );
+    }
+
+    #[cfg(unix)]
+    mod script {
+        use super::*;
+        use std::env;
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::PathBuf;
+        use std::process;
+
+        /// Writes the shell script which logs requests to `<name>.log` and answers them.
+        fn plugin_script(name: &str, answer: &str) -> (PathBuf, PathBuf) {
+            let dir = env::temp_dir().join(format!("transition-plugin-{}-{name}", process::id()));
+            fs::create_dir_all(&dir).expect("cannot create plugin dir");
+            let script = dir.join(format!("{PROGRAM_PREFIX}{name}"));
+            let log = dir.join(format!("{name}.log"));
+            let content = format!(
+                "#!/bin/sh\nwhile read -r line; do\n  echo \"$line\" >> \"{}\"\n  {answer}\ndone\n",
+                log.display()
+            );
+            fs::write(&script, content).expect("cannot write plugin");
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+                .expect("cannot make plugin executable");
+            (script, log)
+        }
+
+        #[test]
+        fn test_commands_are_streamed_to_plugin() -> Result<(), TransitionErr> {
+            let answer = r#"case "$line" in *pattern*) echo '{"ok": true, "playing": true}';; *) echo '{"ok": true}';; esac"#;
+            let (script, log) = plugin_script("spy", answer);
+            let plugin = Plugin::command(&script);
+
+            plugin.set((255, 165, 0), Duration::from_millis(500))?;
+            plugin.state(State::Success)?;
+            let playing = plugin.play_pattern(&[((0, 0, 255), Duration::from_millis(100))])?;
+            drop(plugin);
+
+            assert!(playing, "Plugin plays the pattern");
+            assert_eq!(
+                fs::read_to_string(&log)?.lines().collect::<Vec<_>>(),
+                vec![
+                    r#"{"command":"hello","protocol":1}"#,
+                    r##"{"command":"color","rgb":[255,165,0],"hex":"#ffa500","fade_ms":500}"##,
+                    r#"{"command":"state","state":"success"}"#,
+                    r#"{"command":"pattern","lines":[{"rgb":[0,0,255],"fade_ms":100}]}"#,
+                ]
+            );
+            fs::remove_dir_all(script.parent().expect("plugin dir"))?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_plugin_errors_are_reported() -> Result<(), TransitionErr> {
+            let answer = r#"case "$line" in *hello*) echo '{"ok":true}';; *) echo '{"ok":false,"error":"lamp is offline"}';; esac"#;
+            let (script, log) = plugin_script("broken", answer);
+            let plugin = Plugin::command(&script);
+
+            let first = plugin.set((255, 0, 0), Duration::ZERO);
+            let second = plugin.set((0, 0, 0), Duration::ZERO);
+            drop(plugin);
+
+            for result in [first, second] {
+                assert!(matches!(result, Err(TransitionErr::Plugin(e)) if e == "lamp is offline"));
+            }
+            assert_eq!(
+                fs::read_to_string(&log)?.matches("hello").count(),
+                1,
+                "Plugin was not restarted"
+            );
+            fs::remove_dir_all(script.parent().expect("plugin dir"))?;
+            Ok(())
+        }
+    }
+}
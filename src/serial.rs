@@ -0,0 +1,241 @@
+use crate::error::TransitionErr;
+use crate::light::Light;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+/// Format of the messages sent by [`SerialLed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialProtocol {
+    /// One line per color change: `#RRGGBB FADE\n`, where `RRGGBB` is the hex color and `FADE`
+    /// is the fade time in milliseconds, e.g. `#ff8000 500`. Easy to parse with
+    /// `Serial.readStringUntil('\n')` and to debug with a serial monitor.
+    Text,
+
+    /// Fixed size frames of 6 bytes: `'C'`, red, green, blue and the fade time in milliseconds as
+    /// big-endian `u16` (saturated at 65535 ms). Can be read with `Serial.readBytes(frame, 6)`.
+    Binary,
+}
+
+impl SerialProtocol {
+    fn encode(self, (r, g, b): (u8, u8, u8), fade: Duration) -> Vec<u8> {
+        let fade_ms = u16::try_from(fade.as_millis()).unwrap_or(u16::MAX);
+        match self {
+            SerialProtocol::Text => format!("#{r:02x}{g:02x}{b:02x} {fade_ms}\n").into_bytes(),
+            SerialProtocol::Binary => {
+                let [fade_hi, fade_lo] = fade_ms.to_be_bytes();
+                vec![b'C', r, g, b, fade_hi, fade_lo]
+            }
+        }
+    }
+}
+
+/// Home-made indicator (e.g. WS2812 strip driven by Arduino) listening on a serial port.
+///
+/// Each color change is sent as one message of the chosen [`SerialProtocol`] - the
+/// microcontroller is responsible for the fade. On Unix the port is switched to raw mode with
+/// the configured baud rate (115200 by default). The port is opened with the first color and
+/// reopened after a write error, e.g. when the board was reconnected.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, SerialLed, SerialProtocol, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let led = SerialLed::new("/dev/ttyACM0").protocol(SerialProtocol::Binary);
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], led).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SerialLed {
+    path: PathBuf,
+    baud_rate: u32,
+    protocol: SerialProtocol,
+    port: Mutex<Option<File>>,
+}
+
+impl SerialLed {
+    /// Creates the LED connected to the serial device at `path` (e.g. `/dev/ttyUSB0` or `COM3`),
+    /// using [`SerialProtocol::Text`].
+    #[must_use]
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            baud_rate: DEFAULT_BAUD_RATE,
+            protocol: SerialProtocol::Text,
+            port: Mutex::new(None),
+        }
+    }
+
+    /// Allows to change the baud rate. Supported rates are 9600, 19200, 38400, 57600, 115200
+    /// and 230400.
+    #[must_use]
+    pub fn baud_rate(mut self, baud_rate: u32) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Allows to change the format of the messages.
+    #[must_use]
+    pub fn protocol(mut self, protocol: SerialProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    fn open(&self) -> io::Result<File> {
+        let port = OpenOptions::new().write(true).open(&self.path)?;
+        configure(&port, self.baud_rate)?;
+        Ok(port)
+    }
+}
+
+impl Light for SerialLed {
+    fn set(&self, rgb: (u8, u8, u8), fade: Duration) -> Result<(), TransitionErr> {
+        let mut port = self.port.lock().expect("poisoned lock");
+        if port.is_none() {
+            *port = Some(self.open()?);
+        }
+        let message = self.protocol.encode(rgb, fade);
+        if let Some(opened) = port.as_mut() {
+            if let Err(e) = opened.write_all(&message).and_then(|()| opened.flush()) {
+                *port = None;
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Switches the port to raw mode (no echo, no translation of new lines) with given baud rate.
+#[cfg(unix)]
+fn configure(port: &File, baud_rate: u32) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let speed = match baud_rate {
+        9600 => libc::B9600,
+        19_200 => libc::B19200,
+        38_400 => libc::B38400,
+        57_600 => libc::B57600,
+        115_200 => libc::B115200,
+        230_400 => libc::B230400,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported baud rate: {}", baud_rate),
+            ))
+        }
+    };
+    let fd = port.as_raw_fd();
+    // SAFETY: `fd` is a valid descriptor owned by `port` and `termios` is initialized by
+    // `tcgetattr` before it is modified.
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut termios);
+        if libc::cfsetospeed(&mut termios, speed) != 0
+            || libc::cfsetispeed(&mut termios, speed) != 0
+            || libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn configure(_port: &File, _baud_rate: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_text_protocol() {
+        let message = SerialProtocol::Text.encode((255, 128, 0), Duration::from_millis(500));
+
+        assert_eq!(message, b"#ff8000 500\n");
+    }
+
+    #[test]
+    fn test_binary_protocol_saturates_fade() {
+        let message = SerialProtocol::Binary.encode((1, 2, 3), Duration::from_secs(100));
+
+        assert_eq!(message, vec![b'C', 1, 2, 3, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_missing_port_is_an_error() {
+        let led = SerialLed::new("/dev/transition-missing-port");
+
+        assert!(matches!(
+            led.set((255, 0, 0), Duration::ZERO),
+            Err(TransitionErr::Io(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    mod pty {
+        use super::*;
+        use std::ffi::CStr;
+        use std::io::Read;
+        use std::os::unix::io::FromRawFd;
+
+        /// Opens the pseudo-terminal pair - the master end and the path of the slave end.
+        fn open_pty() -> (File, PathBuf) {
+            // SAFETY: the master descriptor is checked before use and `ptsname` returns a
+            // null-terminated string which is copied before the next call.
+            unsafe {
+                let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                assert!(master >= 0, "cannot open pseudo-terminal");
+                assert_eq!(libc::grantpt(master), 0);
+                assert_eq!(libc::unlockpt(master), 0);
+                let name = CStr::from_ptr(libc::ptsname(master));
+                let path = PathBuf::from(name.to_string_lossy().to_string());
+                (File::from_raw_fd(master), path)
+            }
+        }
+
+        #[test]
+        fn test_colors_are_sent_through_serial_port() -> Result<(), TransitionErr> {
+            let (mut master, slave) = open_pty();
+            let led = SerialLed::new(slave).protocol(SerialProtocol::Binary);
+
+            led.set((10, 20, 30), Duration::from_millis(300))?;
+            led.set((0, 0, 0), Duration::ZERO)?;
+
+            let mut received = [0; 12];
+            master.read_exact(&mut received)?;
+            assert_eq!(
+                received,
+                [b'C', 10, 20, 30, 0x01, 0x2C, b'C', 0, 0, 0, 0, 0],
+                "new line byte (red = 0x0A) was not translated"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn test_unsupported_baud_rate_is_an_error() {
+            let (_master, slave) = open_pty();
+            let led = SerialLed::new(slave).baud_rate(1234);
+
+            assert!(matches!(
+                led.set((255, 0, 0), Duration::ZERO),
+                Err(TransitionErr::Io(_))
+            ));
+        }
+    }
+}
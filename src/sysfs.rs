@@ -0,0 +1,210 @@
+use crate::error::TransitionErr;
+use crate::light::Light;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+const LEDS_ROOT: &str = "/sys/class/leds";
+
+/// LED exposed by the Linux kernel under `/sys/class/leds` (keyboard, chassis or Raspberry Pi
+/// status LEDs).
+///
+/// Multicolor LEDs (with `multi_intensity` file) display the color, other LEDs display its
+/// brightness. The kernel trigger of the LED (e.g. `heartbeat`) is turned off when the color is
+/// set for the first time. The LED cannot fade - use
+/// [`interpolate`](crate::Transition::interpolate()) for smooth changes of "pending" colors.
+///
+/// Writing to sysfs usually requires root rights or udev rules.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use transition::{Led, SysfsLed, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], SysfsLed::new("input3::capslock")).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SysfsLed {
+    path: PathBuf,
+    trigger_disabled: AtomicBool,
+}
+
+impl SysfsLed {
+    /// Creates the LED with given `name` - the name of the directory in `/sys/class/leds`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            path: Path::new(LEDS_ROOT).join(name),
+            trigger_disabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Allows to look for the LED in `root` instead of `/sys/class/leds`.
+    #[must_use]
+    pub fn root<P: AsRef<Path>>(mut self, root: P) -> Self {
+        let name = self.path.file_name().map(PathBuf::from).unwrap_or_default();
+        self.path = root.as_ref().join(name);
+        self
+    }
+
+    fn read(&self, file: &str) -> io::Result<String> {
+        Ok(fs::read_to_string(self.path.join(file))?.trim().to_string())
+    }
+
+    fn write(&self, file: &str, value: &str) -> io::Result<()> {
+        fs::write(self.path.join(file), value)
+    }
+
+    fn disable_trigger(&self) -> io::Result<()> {
+        if self.trigger_disabled.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if self.path.join("trigger").exists() {
+            self.write("trigger", "none")?;
+        }
+        // set only when written, so the failed write is retried with the next color
+        self.trigger_disabled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn max_brightness(&self) -> io::Result<u32> {
+        self.read("max_brightness")?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Intensities of the multicolor LED components, in order of `multi_index` (e.g.
+    /// `red green blue`). Components other than red, green and blue are turned off.
+    fn intensities(&self, (r, g, b): (u8, u8, u8), max_brightness: u32) -> io::Result<String> {
+        let intensities: Vec<String> = self
+            .read("multi_index")?
+            .split_whitespace()
+            .map(|component| match component {
+                "red" => r,
+                "green" => g,
+                "blue" => b,
+                _ => 0,
+            })
+            .map(|intensity| (u32::from(intensity) * max_brightness / 255).to_string())
+            .collect();
+        Ok(intensities.join(" "))
+    }
+}
+
+impl Light for SysfsLed {
+    fn set(&self, rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+        self.disable_trigger()?;
+        let max_brightness = self.max_brightness()?;
+        let brightness = if self.path.join("multi_intensity").exists() {
+            self.write("multi_intensity", &self.intensities(rgb, max_brightness)?)?;
+            if rgb == (0, 0, 0) {
+                0
+            } else {
+                max_brightness
+            }
+        } else {
+            let (r, g, b) = rgb;
+            u32::from(r.max(g).max(b)) * max_brightness / 255
+        };
+        self.write("brightness", &brightness.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn fake_led(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = env::temp_dir().join(format!("transition-sysfs-{}-{name}", process::id()));
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).expect("cannot create fake LED");
+        for (file, content) in files {
+            fs::write(dir.join(file), content).expect("cannot create fake LED file");
+        }
+        root
+    }
+
+    fn content(root: &Path, name: &str, file: &str) -> String {
+        fs::read_to_string(root.join(name).join(file)).expect("cannot read fake LED file")
+    }
+
+    #[test]
+    fn test_single_color_led_displays_brightness() -> Result<(), TransitionErr> {
+        let root = fake_led(
+            "led0",
+            &[
+                ("brightness", "0"),
+                ("max_brightness", "255"),
+                ("trigger", "[mmc0] none"),
+            ],
+        );
+        let led = SysfsLed::new("led0").root(&root);
+
+        led.set((0, 128, 64), Duration::ZERO)?;
+
+        assert_eq!(content(&root, "led0", "brightness"), "128");
+        assert_eq!(content(&root, "led0", "trigger"), "none");
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_multicolor_led_displays_color() -> Result<(), TransitionErr> {
+        let root = fake_led(
+            "rgb:status",
+            &[
+                ("brightness", "0"),
+                ("max_brightness", "255"),
+                ("multi_index", "green red blue\n"),
+                ("multi_intensity", "0 0 0"),
+            ],
+        );
+        let led = SysfsLed::new("rgb:status").root(&root);
+
+        led.set((255, 165, 0), Duration::ZERO)?;
+
+        assert_eq!(content(&root, "rgb:status", "multi_intensity"), "165 255 0");
+        assert_eq!(content(&root, "rgb:status", "brightness"), "255");
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_trigger_write_is_retried() -> Result<(), TransitionErr> {
+        let root = fake_led("led1", &[("brightness", "0"), ("max_brightness", "255")]);
+        let trigger = root.join("led1").join("trigger");
+        fs::create_dir(&trigger)?; // cannot be written
+        let led = SysfsLed::new("led1").root(&root);
+
+        let first = led.set((255, 0, 0), Duration::ZERO);
+        fs::remove_dir(&trigger)?;
+        fs::write(&trigger, "[heartbeat] none")?;
+        led.set((255, 0, 0), Duration::ZERO)?;
+
+        assert!(matches!(first, Err(TransitionErr::Io(_))));
+        assert_eq!(content(&root, "led1", "trigger"), "none");
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_led_is_an_error() {
+        let led = SysfsLed::new("missing").root(env::temp_dir());
+
+        assert!(matches!(
+            led.set((255, 0, 0), Duration::ZERO),
+            Err(TransitionErr::Io(_))
+        ));
+    }
+}
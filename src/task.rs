@@ -1,13 +1,27 @@
+use crate::backend::Backend;
+use crate::color::Color;
 use crate::color::Led;
+use crate::effect::Effect;
 use crate::error::TransitionErr;
-use blinkrs::Blinkers;
-use blinkrs::Message as BlinkMsg;
+use crate::interpolation;
+use crate::interpolation::Easing;
+use crate::msg;
+use crate::msg::BlinkMsg;
 use std::fmt::Debug;
 use std::time::Duration;
 
 pub(crate) trait Task: Send + Sync {
-    fn execute(&self) -> Result<(), TransitionErr>;
+    /// Plays one execution of the task. Holds between the messages are spent in `wait`, which
+    /// returns `true` when the task should stop early.
+    fn execute(
+        &self,
+        backend: &dyn Backend,
+        wait: &dyn Fn(Duration) -> bool,
+    ) -> Result<(), TransitionErr>;
     fn get(&self) -> &[BlinkMsg];
+
+    /// Messages of one execution, each followed by the time to wait before the next one.
+    fn frames(&self) -> Vec<(BlinkMsg, Duration)>;
 }
 
 impl Debug for dyn Task {
@@ -16,44 +30,84 @@ impl Debug for dyn Task {
     }
 }
 
+const STEP: Duration = Duration::from_millis(500);
+const FRAME: Duration = Duration::from_millis(20);
+const FRAMES_PER_STEP: u16 = 25;
+
+#[derive(Debug)]
 pub(crate) struct BlinkTask {
-    blinkers: Blinkers,
     transition: Vec<BlinkMsg>,
+    holds: Vec<Duration>,
 }
 
 impl BlinkTask {
-    pub fn new(colors: &[Led]) -> Result<Self, TransitionErr> {
+    pub fn new(colors: &[Led]) -> Self {
         let mut transition = Vec::new();
-        let blinkers: Blinkers = Blinkers::new()?;
         for color in colors {
-            transition.push(BlinkMsg::Fade(
-                color.into(),
-                Duration::from_millis(500),
-                None,
-            ));
+            transition.push(BlinkMsg::Fade(color.into(), STEP, None));
+        }
+        let holds = vec![STEP; transition.len()];
+        Self { transition, holds }
+    }
+
+    pub(crate) fn effect(effect: &Effect) -> Self {
+        Self::from_frames(effect.frames())
+    }
+
+    pub(crate) fn from_frames(frames: Vec<(BlinkMsg, Duration)>) -> Self {
+        let (transition, holds) = frames.into_iter().unzip();
+        Self { transition, holds }
+    }
+
+    /// Creates task which fades between colors of `messages` in software, sending many
+    /// intermediate frames instead of relying on the device fade.
+    pub(crate) fn interpolated(messages: &[BlinkMsg], easing: Easing) -> Self {
+        let colors: Vec<(u8, u8, u8)> = messages.iter().map(|&msg| msg::rgb(msg)).collect();
+        let mut transition = Vec::new();
+        for (idx, &from) in colors.iter().enumerate() {
+            let to = colors[(idx + 1) % colors.len()];
+            for (r, g, b) in interpolation::frames(from, to, FRAMES_PER_STEP, easing) {
+                transition.push(BlinkMsg::Fade(Color::Three(r, g, b), FRAME, None));
+            }
         }
-        Ok(Self {
-            blinkers,
-            transition,
-        })
+        let holds = vec![FRAME; transition.len()];
+        Self { transition, holds }
     }
 
-    fn play_transition(&self) -> Result<(), TransitionErr> {
-        for &message in &self.transition {
-            self.blinkers.send(message)?;
-            std::thread::sleep(Duration::from_millis(500));
+    fn play_transition(
+        &self,
+        backend: &dyn Backend,
+        wait: &dyn Fn(Duration) -> bool,
+    ) -> Result<(), TransitionErr> {
+        for (&message, &hold) in self.transition.iter().zip(&self.holds) {
+            backend.send(message)?;
+            if wait(hold) {
+                break;
+            }
         }
         Ok(())
     }
 }
 
 impl Task for BlinkTask {
-    fn execute(&self) -> Result<(), TransitionErr> {
-        self.play_transition()?;
+    fn execute(
+        &self,
+        backend: &dyn Backend,
+        wait: &dyn Fn(Duration) -> bool,
+    ) -> Result<(), TransitionErr> {
+        self.play_transition(backend, wait)?;
         Ok(())
     }
 
     fn get(&self) -> &[BlinkMsg] {
         &self.transition
     }
+
+    fn frames(&self) -> Vec<(BlinkMsg, Duration)> {
+        self.transition
+            .iter()
+            .copied()
+            .zip(self.holds.iter().copied())
+            .collect()
+    }
 }
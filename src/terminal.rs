@@ -0,0 +1,340 @@
+use crate::error::TransitionErr;
+use crate::light::Light;
+use log::debug;
+use std::env;
+use std::fmt;
+use std::io;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Width of the block displayed in the status line, in characters.
+const BLOCK_WIDTH: usize = 4;
+
+/// Saves the window title on the terminal's stack (xterm).
+const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Restores the window title saved with [`PUSH_TITLE`].
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Standard 16 terminal colors (xterm palette), in order of their codes.
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Colored squares which can be displayed in the window title.
+const SQUARES: [((u8, u8, u8), char); 9] = [
+    ((0, 0, 0), '⬛'),
+    ((255, 255, 255), '⬜'),
+    ((255, 0, 0), '🟥'),
+    ((255, 165, 0), '🟧'),
+    ((255, 255, 0), '🟨'),
+    ((0, 255, 0), '🟩'),
+    ((0, 0, 255), '🟦'),
+    ((128, 0, 128), '🟪'),
+    ((139, 69, 19), '🟫'),
+];
+
+/// Where the [`Terminal`] displays the color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalMode {
+    /// Colored block in the bottom-left corner of the terminal. The cursor is restored after
+    /// drawing, so the output of the program is not disturbed.
+    StatusLine,
+
+    /// Colored square in the window (or tab) title. The original title is restored when the
+    /// [`Terminal`] is dropped, by terminals which can save it (e.g. xterm, VTE based ones).
+    Title,
+}
+
+/// Number of colors supported by the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit colors.
+    TrueColor,
+    /// 256 colors palette.
+    Ansi256,
+    /// 16 standard colors.
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guesses the color depth from `COLORTERM` and `TERM` environment variables.
+    fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Escape sequence which sets the background color.
+    fn background(self, (r, g, b): (u8, u8, u8)) -> String {
+        match self {
+            ColorDepth::TrueColor => format!("\x1b[48;2;{r};{g};{b}m"),
+            ColorDepth::Ansi256 => format!("\x1b[48;5;{}m", ansi_256((r, g, b))),
+            ColorDepth::Ansi16 => {
+                let code = nearest(&ANSI_16, (r, g, b));
+                let base = if code < 8 { 40 } else { 100 - 8 };
+                format!("\x1b[{}m", base + code)
+            }
+        }
+    }
+}
+
+/// Virtual LED rendered in the terminal with ANSI escape sequences, handy when working over SSH
+/// or without the device.
+///
+/// The terminal cannot fade - use [`interpolate`](crate::Transition::interpolate()) for smooth
+/// changes of "pending" colors. When the standard error is not a terminal (e.g. it's redirected
+/// to CI logs), nothing is written.
+///
+/// # Example
+/// ```
+/// # use std::error::Error;
+/// use transition::{Led, Terminal, TerminalMode, Transition};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let terminal = Terminal::new(TerminalMode::StatusLine);
+/// let notifier = Transition::with_light(&[Led::Blue, Led::Blank], terminal).start()?;
+/// notifier.notify_success()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Terminal {
+    mode: TerminalMode,
+    depth: ColorDepth,
+    out: Mutex<Output>,
+}
+
+/// Where the escape sequences are written, `None` when there is no terminal.
+struct Output {
+    writer: Option<Box<dyn Write + Send>>,
+    title_saved: bool,
+}
+
+impl Output {
+    fn new(writer: Option<Box<dyn Write + Send>>) -> Self {
+        Self {
+            writer,
+            title_saved: false,
+        }
+    }
+}
+
+impl Terminal {
+    /// Creates the virtual LED which writes to the standard error. The color depth is detected
+    /// from `COLORTERM` and `TERM` environment variables.
+    #[must_use]
+    pub fn new(mode: TerminalMode) -> Self {
+        let stderr = io::stderr();
+        let writer: Option<Box<dyn Write + Send>> = if stderr.is_terminal() {
+            Some(Box::new(stderr))
+        } else {
+            debug!("standard error is not a terminal, colors are not displayed");
+            None
+        };
+        Self {
+            mode,
+            depth: ColorDepth::detect(),
+            out: Mutex::new(Output::new(writer)),
+        }
+    }
+
+    /// Allows to override the detected color depth.
+    #[must_use]
+    pub fn color_depth(mut self, depth: ColorDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Allows to write escape sequences somewhere else than to the standard error (e.g. to
+    /// `/dev/tty`).
+    #[must_use]
+    pub fn writer<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.out = Mutex::new(Output::new(Some(Box::new(writer))));
+        self
+    }
+
+    fn render(&self, rgb: (u8, u8, u8)) -> String {
+        match self.mode {
+            TerminalMode::StatusLine => format!(
+                "\x1b7\x1b[999;1H{}{}\x1b[0m\x1b8",
+                self.depth.background(rgb),
+                " ".repeat(BLOCK_WIDTH)
+            ),
+            TerminalMode::Title => {
+                let square = SQUARES[nearest(&SQUARES.map(|(color, _)| color), rgb)].1;
+                format!("\x1b]2;{square} transition\x07")
+            }
+        }
+    }
+}
+
+impl Light for Terminal {
+    fn set(&self, rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+        let mut out = self.out.lock().expect("poisoned lock");
+        let save_title = self.mode == TerminalMode::Title && !out.title_saved;
+        let Some(writer) = out.writer.as_mut() else {
+            return Ok(());
+        };
+        if save_title {
+            writer.write_all(PUSH_TITLE.as_bytes())?;
+        }
+        writer.write_all(self.render(rgb).as_bytes())?;
+        writer.flush()?;
+        out.title_saved |= save_title;
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let out = self.out.get_mut().expect("poisoned lock");
+        if let (true, Some(writer)) = (out.title_saved, out.writer.as_mut()) {
+            let _ = writer
+                .write_all(POP_TITLE.as_bytes())
+                .and_then(|()| writer.flush());
+        }
+    }
+}
+
+impl fmt::Debug for Terminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Terminal")
+            .field("mode", &self.mode)
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Code of the closest color of the 256 colors palette - from the 6x6x6 cube or the gray ramp.
+#[allow(clippy::cast_possible_truncation)]
+fn ansi_256((r, g, b): (u8, u8, u8)) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            _ => 232 + ((u16::from(r) - 8) * 24 / 240) as u8,
+        };
+    }
+    let level = |c: u8| ((u16::from(c) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Index of the color closest to `rgb`.
+fn nearest(palette: &[(u8, u8, u8)], (r, g, b): (u8, u8, u8)) -> usize {
+    let distance = |&(pr, pg, pb): &(u8, u8, u8)| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        d(pr, r) + d(pg, g) + d(pb, b)
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| distance(color))
+        .map_or(0, |(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Screen(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().expect("poisoned lock").write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Screen {
+        fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().expect("poisoned lock")).to_string()
+        }
+    }
+
+    fn terminal(mode: TerminalMode, depth: ColorDepth) -> (Terminal, Screen) {
+        let screen = Screen::default();
+        let terminal = Terminal::new(mode)
+            .color_depth(depth)
+            .writer(screen.clone());
+        (terminal, screen)
+    }
+
+    #[test]
+    fn test_status_line_uses_true_color() -> Result<(), TransitionErr> {
+        let (terminal, screen) = terminal(TerminalMode::StatusLine, ColorDepth::TrueColor);
+
+        terminal.set((255, 165, 0), Duration::ZERO)?;
+
+        assert!(screen.text().contains("\x1b[48;2;255;165;0m"));
+        assert!(
+            screen.text().ends_with("\x1b[0m\x1b8"),
+            "Cursor was restored"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_colors() {
+        assert_eq!(
+            ColorDepth::Ansi256.background((255, 0, 0)),
+            "\x1b[48;5;196m"
+        );
+        assert_eq!(
+            ColorDepth::Ansi256.background((128, 128, 128)),
+            "\x1b[48;5;244m"
+        );
+        assert_eq!(ColorDepth::Ansi16.background((0, 0, 0)), "\x1b[40m");
+        assert_eq!(ColorDepth::Ansi16.background((0, 250, 0)), "\x1b[102m");
+    }
+
+    #[test]
+    fn test_title_shows_closest_square() -> Result<(), TransitionErr> {
+        let (terminal, screen) = terminal(TerminalMode::Title, ColorDepth::Ansi16);
+
+        terminal.set((10, 200, 30), Duration::ZERO)?;
+
+        assert_eq!(screen.text(), "\x1b[22;0t\x1b]2;🟩 transition\x07");
+        Ok(())
+    }
+
+    #[test]
+    fn test_title_is_restored_when_dropped() -> Result<(), TransitionErr> {
+        let (terminal, screen) = terminal(TerminalMode::Title, ColorDepth::Ansi16);
+
+        terminal.set((255, 0, 0), Duration::ZERO)?;
+        terminal.set((0, 255, 0), Duration::ZERO)?;
+        drop(terminal);
+
+        assert_eq!(screen.text().matches(PUSH_TITLE).count(), 1);
+        assert!(screen.text().ends_with(POP_TITLE), "Title was restored");
+        Ok(())
+    }
+}
@@ -1,11 +1,19 @@
 #[cfg(test)]
 pub(crate) mod utils {
+    use crate::backend::Backend;
     use crate::error::TransitionErr;
+    use crate::msg::BlinkMsg;
     use crate::msg::Message;
     use crate::task::Task;
-    use blinkrs::Message as BlinkMsg;
+    use crossbeam_channel::Receiver;
+    use std::io::Read;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::net::TcpStream;
     use std::sync::atomic::AtomicBool;
     use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::Duration;
 
     pub(crate) struct TaskSpy {
         task_executed: AtomicBool,
@@ -24,7 +32,11 @@ pub(crate) mod utils {
     }
 
     impl Task for TaskSpy {
-        fn execute(&self) -> Result<(), TransitionErr> {
+        fn execute(
+            &self,
+            _backend: &dyn Backend,
+            _wait: &dyn Fn(Duration) -> bool,
+        ) -> Result<(), TransitionErr> {
             self.task_executed.store(true, Ordering::SeqCst);
             Ok(())
         }
@@ -32,6 +44,10 @@ pub(crate) mod utils {
         fn get(&self) -> &[BlinkMsg] {
             unimplemented!("not needed here")
         }
+
+        fn frames(&self) -> Vec<(BlinkMsg, Duration)> {
+            Vec::new()
+        }
     }
 
     pub(crate) struct MessageSpy {
@@ -51,13 +67,204 @@ pub(crate) mod utils {
     }
 
     impl Message for MessageSpy {
-        fn send(&self) -> Result<(), TransitionErr> {
+        fn send(&self, _backend: &dyn Backend) -> Result<(), TransitionErr> {
             self.message_sent.store(true, Ordering::SeqCst);
             Ok(())
         }
 
         fn get(&self) -> BlinkMsg {
-            unimplemented!("not needed here")
+            BlinkMsg::Off
+        }
+    }
+
+    #[cfg(feature = "blink1")]
+    pub(crate) use fake::FakeTransport;
+
+    #[cfg(feature = "blink1")]
+    mod fake {
+        use crate::error::TransitionErr;
+        use crate::pattern::MAX_LINES;
+        use crate::transport::Report;
+        use crate::transport::Transport;
+        use crate::transport::REPORT_ID;
+        use crate::transport::REPORT_SIZE;
+        use std::sync::Mutex;
+
+        type Rgb = (u8, u8, u8);
+
+        #[derive(Debug, Default)]
+        struct DeviceState {
+            rgb: Rgb,
+            lines: Vec<(Rgb, u16)>,
+            playing: Option<u8>,
+            tickle: Option<(u16, u8, u8)>,
+            saved: bool,
+            commands: Vec<u8>,
+            response: Report,
+        }
+
+        /// In-memory blink(1) which decodes the reports and remembers their effect.
+        #[derive(Debug)]
+        pub(crate) struct FakeTransport {
+            serial: String,
+            version: u16,
+            state: Mutex<DeviceState>,
+        }
+
+        impl FakeTransport {
+            pub(crate) fn new(serial: &str, version: u16) -> Self {
+                let state = DeviceState {
+                    lines: vec![((0, 0, 0), 0); MAX_LINES],
+                    ..DeviceState::default()
+                };
+                Self {
+                    serial: serial.to_string(),
+                    version,
+                    state: Mutex::new(state),
+                }
+            }
+
+            fn state(&self) -> std::sync::MutexGuard<'_, DeviceState> {
+                self.state.lock().expect("poisoned lock")
+            }
+
+            pub(crate) fn rgb(&self) -> Rgb {
+                self.state().rgb
+            }
+
+            /// Color and fade time (in units of 10 ms) of the pattern line.
+            pub(crate) fn line(&self, position: usize) -> (Rgb, u16) {
+                self.state().lines[position]
+            }
+
+            /// End of the played pattern, `None` when the pattern is not played.
+            pub(crate) fn playing(&self) -> Option<u8> {
+                self.state().playing
+            }
+
+            /// Timeout (in units of 10 ms) and the "crashed" pattern of the armed watchdog.
+            pub(crate) fn tickle(&self) -> Option<(u16, u8, u8)> {
+                self.state().tickle
+            }
+
+            pub(crate) fn saved(&self) -> bool {
+                self.state().saved
+            }
+
+            /// Commands of all written reports, in order.
+            pub(crate) fn commands(&self) -> Vec<u8> {
+                self.state().commands.clone()
+            }
+        }
+
+        impl Transport for FakeTransport {
+            fn write(&self, report: &Report) -> Result<(), TransitionErr> {
+                assert_eq!(report[0], REPORT_ID, "Unexpected report id");
+                let args = &report[2..];
+                let mut state = self.state();
+                let mut response = [0; REPORT_SIZE];
+                response[..2].copy_from_slice(&report[..2]);
+                state.commands.push(report[1]);
+                match report[1] {
+                    b'c' | b'n' => state.rgb = (args[0], args[1], args[2]),
+                    b'P' => {
+                        let time = u16::from_be_bytes([args[3], args[4]]);
+                        state.lines[usize::from(args[5])] = ((args[0], args[1], args[2]), time);
+                    }
+                    b'p' => state.playing = (args[0] == 1).then_some(args[2]),
+                    b'D' => {
+                        let timeout = u16::from_be_bytes([args[1], args[2]]);
+                        state.tickle = (args[0] == 1).then_some((timeout, args[4], args[5]));
+                    }
+                    b'W' => state.saved = args[..4] == [0xBE, 0xEF, 0xCA, 0xFE],
+                    b'B' => {}
+                    b'r' => {
+                        let (r, g, b) = state.rgb;
+                        response[2..5].copy_from_slice(&[r, g, b]);
+                    }
+                    b'v' => {
+                        let [major, minor] = [self.version / 100, self.version % 100];
+                        response[3] = b'0' + u8::try_from(major).expect("invalid version");
+                        response[4] = b'0' + u8::try_from(minor).expect("invalid version");
+                    }
+                    command => panic!("unknown command: {}", char::from(command)),
+                }
+                state.response = response;
+                Ok(())
+            }
+
+            fn read(&self) -> Result<Report, TransitionErr> {
+                Ok(self.state().response)
+            }
+
+            fn serial(&self) -> Result<String, TransitionErr> {
+                Ok(self.serial.clone())
+            }
+        }
+    }
+
+    /// HTTP server which passes received requests to the test and responds with `status` and
+    /// `body` to all of them.
+    pub(crate) struct HttpStub {
+        url: String,
+        requests: Receiver<String>,
+    }
+
+    impl HttpStub {
+        pub(crate) fn start(status: u16, body: &'static str) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("cannot start HTTP stub");
+            let url = format!("http://{}", listener.local_addr().expect("no address"));
+            let (sender, requests) = crossbeam_channel::unbounded();
+            thread::spawn(move || {
+                for mut stream in listener.incoming().flatten() {
+                    let request = read_request(&mut stream);
+                    let response = format!(
+                        "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream
+                        .write_all(response.as_bytes())
+                        .expect("cannot respond");
+                    if sender.send(request).is_err() {
+                        break;
+                    }
+                }
+            });
+            Self { url, requests }
+        }
+
+        pub(crate) fn url(&self) -> &str {
+            &self.url
+        }
+
+        /// Next received request - the request line, headers and body.
+        pub(crate) fn request(&self) -> String {
+            self.requests
+                .recv_timeout(Duration::from_secs(1))
+                .expect("request not received")
+        }
+    }
+
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        loop {
+            let read = stream.read(&mut buf).expect("cannot read request");
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .and_then(|length| length.parse().ok())
+                    .unwrap_or(0);
+                if body.len() >= length || read == 0 {
+                    return text;
+                }
+            }
+            if read == 0 {
+                return text;
+            }
         }
     }
 
@@ -1,23 +1,63 @@
+use crate::backend;
+use crate::backend::Backend;
+use crate::backend::Tracked;
+use crate::brightness::Dimmed;
+use crate::brightness::QuietHours;
+use crate::calibration::Calibrated;
+use crate::calibration::Calibration;
+use crate::code::BlinkCode;
+use crate::color::Color;
 use crate::color::Led;
+use crate::decay;
+use crate::decay::Decay;
+use crate::effect::Effect;
 use crate::error::TransitionErr;
+use crate::fanout::ErrorPolicy;
+use crate::fanout::FanOut;
+use crate::fanout::Sink;
+use crate::flash::FlashGuard;
+use crate::interpolation::Easing;
+use crate::light::Light;
+use crate::light::LightBackend;
+use crate::light::State;
+use crate::msg;
+use crate::msg::BlinkMsg;
 use crate::msg::ColorMessage;
+use crate::msg::EffectMessage;
 use crate::msg::Message;
 use crate::notifier::MsgType;
 use crate::notifier::Notifier;
+use crate::panic_hook;
+use crate::pattern;
 use crate::task::BlinkTask;
 use crate::task::Task;
+use crate::watchdog;
+use crate::watchdog::KeepAlive;
+use crossbeam_channel::select;
 use crossbeam_channel::unbounded;
 use log::debug;
 use log::info;
+use log::warn;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
 /// Main structure. Represents colors of task state (pending, successfull, failed). Allows to start the transition.
 #[derive(Debug, Clone)]
 pub struct Transition {
+    backend: Arc<dyn Backend>,
     task: Arc<dyn Task>,
     failure_msg: Arc<dyn Message>,
     success_msg: Arc<dyn Message>,
+    decay: Option<(Duration, Decay)>,
+    brightness: f32,
+    quiet_hours: Option<QuietHours>,
+    calibration: Option<Calibration>,
+    flash_guard: Option<FlashGuard>,
+    failure_code: BlinkCode,
+    device_pattern: bool,
+    watchdog: Option<(Duration, Arc<dyn Task>)>,
+    lights: Vec<Arc<Sink>>,
 }
 
 impl Transition {
@@ -47,13 +87,74 @@ impl Transition {
     /// # Errors
     ///
     /// If there will be issue with connecting to blink(1) device,
-    /// an error variant will be returned.
+    /// an error variant will be returned. If `TRANSITION_FALLBACK` environment variable is set to
+    /// `log`, the missing device is not an error - see
+    /// [`with_fallback`](Transition::with_fallback()).
     pub fn new(colors: &[Led]) -> Result<Self, TransitionErr> {
-        Ok(Self {
-            task: Arc::new(BlinkTask::new(colors)?),
+        Ok(Self::with_backend(colors, backend::open(false)?))
+    }
+
+    /// Creates new instance of `Transition` which does not require blink(1) device.
+    ///
+    /// Works the same as [`new`](Transition::new()) when the device is connected. When it's
+    /// missing, the transition only logs state changes (using [`log`](https://docs.rs/log) crate)
+    /// and otherwise does nothing. Handy for CI runners or machines without the device.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crate::transition::{Transition, Led};
+    /// # use std::error::Error;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let notifier = Transition::with_fallback(&[Led::Blue, Led::Blank]).start()?;
+    /// notifier.notify_success()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_fallback(colors: &[Led]) -> Self {
+        let backend = backend::open(true).expect("logging fallback cannot fail");
+        Self::with_backend(colors, backend)
+    }
+
+    /// Creates new instance of `Transition` which displays the colors on the [`Light`] instead
+    /// of blink(1) device.
+    ///
+    /// Works the same as [`new`](Transition::new()) - all patterns, outcomes and color
+    /// adjustments are supported, except [`crash_watchdog`](Transition::crash_watchdog()), which
+    /// needs the device memory. [`device_pattern`](Transition::device_pattern()) works only with
+    /// lights which can play patterns on their own (see [`Light::play_pattern`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crate::transition::{Transition, Led, SysfsLed};
+    ///
+    /// let transition = Transition::with_light(&[Led::Blue, Led::Blank], SysfsLed::new("led0"));
+    /// ```
+    #[must_use]
+    pub fn with_light<L: Light + 'static>(colors: &[Led], light: L) -> Self {
+        let backend = Arc::new(Tracked::new(Arc::new(LightBackend::new(light))));
+        Self::with_backend(colors, backend)
+    }
+
+    fn with_backend(colors: &[Led], backend: Arc<dyn Backend>) -> Self {
+        Self {
+            backend,
+            task: Arc::new(BlinkTask::new(colors)),
             failure_msg: Arc::new(ColorMessage::new(&Led::Red)),
             success_msg: Arc::new(ColorMessage::new(&Led::Green)),
-        })
+            decay: None,
+            brightness: 1.0,
+            quiet_hours: None,
+            calibration: None,
+            flash_guard: Some(FlashGuard::default()),
+            failure_code: BlinkCode::default(),
+            device_pattern: false,
+            watchdog: None,
+            lights: Vec::new(),
+        }
     }
 
     /// Starts the transition.
@@ -61,13 +162,21 @@ impl Transition {
     /// The transition is started in a separate thread. As a result, you get
     /// [Notifier](../struct.Notifier) struct.
     ///
+    /// The transition is not consumed, so it can be started many times (e.g. in a loop which
+    /// runs the same job repeatedly). Each call returns a fresh [Notifier](../struct.Notifier).
+    ///
     /// # Example
     /// ```
     /// # use std::error::Error;
     /// use crate::transition::{Transition, Led};
     ///
     /// # fn main() -> Result<(), Box<dyn Error>> {
-    /// let notifier = Transition::new(&[Led::Blue, Led::Blank])?.start()?;
+    /// let transition = Transition::new(&[Led::Blue, Led::Blank])?;
+    /// for _ in 0..3 {
+    ///     let notifier = transition.start()?;
+    ///     // your job here
+    ///     notifier.notify_success()?;
+    /// }
     /// # Ok(())
     /// # }
     /// ```
@@ -75,45 +184,236 @@ impl Transition {
     /// # Errors
     ///
     /// If any error related to Blink(1) device will be thrown, this method results with
-    /// [`TransitionErr`].
-    pub fn start(self) -> Result<Notifier, TransitionErr> {
-        debug!("starting transition");
+    /// [`TransitionErr`]. It also fails when any pattern flashes too fast and
+    /// [`FlashGuard`] is set to reject such patterns.
+    pub fn start(&self) -> Result<Notifier, TransitionErr> {
+        let transition = self.guarded()?;
+        info!("transition started");
+        decay::supersede();
         let (sender, receiver) = unbounded();
+        let output = self.output();
+        let previous = self.previous();
+        let keep_alive = transition.arm_watchdog(&output)?;
+        let on_device = transition.play_on_device(&output)?;
+        output.state(State::Pending)?;
+        let watch = panic_hook::watch();
         debug!("starting thread with task to execute");
-        let handle = thread::spawn(move || loop {
-            match receiver.try_recv() {
-                Ok(MsgType::Success) => break self.send_success_msg(),
-                Ok(MsgType::Failure) => break self.send_failure_msg(),
-                Err(_) => info!("no message received"),
-            };
-            self.execute_task_if_present()?;
-        });
+        let handle = if on_device {
+            thread::spawn(move || {
+                select! {
+                    recv(receiver) -> msg => match msg {
+                        Ok(msg) => {
+                            disarm(keep_alive);
+                            output.stop_pattern()?;
+                            transition.finish(&msg, &output, &previous)
+                        }
+                        Err(_) => Ok(()), // notifier dropped, the device keeps playing the pattern
+                    },
+                    recv(watch.panics()) -> color => {
+                        disarm(keep_alive);
+                        output.stop_pattern()?;
+                        transition.panicked(color.ok().flatten(), &output)
+                    },
+                }
+            })
+        } else {
+            thread::spawn(move || loop {
+                match receiver.try_recv() {
+                    Ok(msg) => {
+                        disarm(keep_alive);
+                        break transition.finish(&msg, &output, &previous);
+                    }
+                    Err(_) => info!("no message received"),
+                };
+                if let Some(color) = watch.panicked() {
+                    disarm(keep_alive);
+                    break transition.panicked(color, &output);
+                }
+                transition.execute_task_if_present(output.as_ref(), &|hold| watch.wait(hold))?;
+            })
+        };
         Ok(Notifier::new(sender, handle))
     }
 
-    fn send_success_msg(&self) -> Result<(), TransitionErr> {
-        self.send_if_present(&MsgType::Success)?;
+    /// Stores the "crashed" pattern in the device and starts tickling it, if requested and
+    /// supported.
+    fn arm_watchdog(&self, output: &Arc<dyn Backend>) -> Result<Option<KeepAlive>, TransitionErr> {
+        let Some((timeout, task)) = &self.watchdog else {
+            return Ok(None);
+        };
+        let pattern = pattern::compile(&task.frames())?;
+        watchdog::arm(output.clone(), *timeout, &pattern)
+    }
+
+    /// Starts the "pending" pattern on the device, if requested and supported.
+    fn play_on_device(&self, output: &Arc<dyn Backend>) -> Result<bool, TransitionErr> {
+        if !self.device_pattern {
+            return Ok(false);
+        }
+        let pattern = pattern::compile(&self.task.frames())?;
+        let on_device = output.play_pattern(&pattern)?;
+        if !on_device {
+            debug!("device cannot store patterns, playing the pattern from host");
+        }
+        Ok(on_device)
+    }
+
+    fn finish(
+        &self,
+        msg: &MsgType,
+        output: &Arc<dyn Backend>,
+        previous: &Previous,
+    ) -> Result<(), TransitionErr> {
+        let state = match msg {
+            MsgType::Success => State::Success,
+            MsgType::Failure | MsgType::FailureWithCode(_) => State::Failure,
+            MsgType::Cancel => State::Cancelled,
+        };
+        match msg {
+            MsgType::Success => self.send_success_msg(output),
+            MsgType::Failure => self.send_failure_msg(output),
+            MsgType::FailureWithCode(_) => self.send_if_present(msg, output),
+            MsgType::Cancel => self.restore(previous),
+        }?;
+        output.state(state)?;
+        // added lights are updated in background, the outcome should be visible when notify returns
+        output.flush()
+    }
+
+    /// Finishes the transition after a panic, see [`install_panic_hook`](crate::install_panic_hook).
+    fn panicked(&self, color: Option<Led>, output: &Arc<dyn Backend>) -> Result<(), TransitionErr> {
+        info!("finishing transition after panic");
+        match color {
+            Some(color) => ColorMessage::new(&color).send(output.as_ref()),
+            None => self.send_failure_msg(output),
+        }?;
+        output.state(State::Failure)?;
+        output.flush()
+    }
+
+    /// Returns the copy of the transition with all patterns checked by the [`FlashGuard`].
+    fn guarded(&self) -> Result<Self, TransitionErr> {
+        let mut transition = self.clone();
+        transition.task = self.guard(self.task.clone())?;
+        if let Some((timeout, task)) = &self.watchdog {
+            transition.watchdog = Some((*timeout, self.guard(task.clone())?));
+        }
+        for message in [&self.success_msg, &self.failure_msg] {
+            if let Some(animation) = message.animation() {
+                self.guard(animation)?;
+            }
+        }
+        Ok(transition)
+    }
+
+    fn guard(&self, task: Arc<dyn Task>) -> Result<Arc<dyn Task>, TransitionErr> {
+        match self.flash_guard {
+            Some(guard) => guard.apply(task),
+            None => Ok(task),
+        }
+    }
+
+    /// Backend with all color adjustments (brightness, calibration) applied, together with the
+    /// added lights, which get the colors without the adjustments of the device.
+    fn output(&self) -> Arc<dyn Backend> {
+        let mut output = self.backend.clone();
+        if let Some(calibration) = self.calibration {
+            output = Arc::new(Calibrated::new(output, calibration));
+        }
+        if self.brightness < 1.0 || self.quiet_hours.is_some() {
+            output = Arc::new(Dimmed::new(output, self.brightness, self.quiet_hours));
+        }
+        self.with_lights(output)
+    }
+
+    fn with_lights(&self, primary: Arc<dyn Backend>) -> Arc<dyn Backend> {
+        if self.lights.is_empty() {
+            return primary;
+        }
+        Arc::new(FanOut::new(primary, self.lights.clone()))
+    }
+
+    fn send_success_msg(&self, output: &Arc<dyn Backend>) -> Result<(), TransitionErr> {
+        self.send_if_present(&MsgType::Success, output)?;
         Ok(())
     }
 
-    fn send_if_present(&self, msg: &MsgType) -> Result<(), TransitionErr> {
-        let message = match msg {
-            MsgType::Success => self.success_msg.as_ref(),
-            MsgType::Failure => self.failure_msg.as_ref(),
+    fn send_if_present(
+        &self,
+        msg: &MsgType,
+        output: &Arc<dyn Backend>,
+    ) -> Result<(), TransitionErr> {
+        debug_assert!(
+            !matches!(msg, MsgType::Cancel),
+            "cancelled transition is restored"
+        );
+        let message = if matches!(msg, MsgType::Success) {
+            self.success_msg.as_ref()
+        } else {
+            self.failure_msg.as_ref()
+        };
+        info!("transition finished: {:?}", msg);
+        if matches!(msg, MsgType::Success) && self.success_suppressed() {
+            debug!("quiet hours, success not displayed");
+            output.send(BlinkMsg::Off)?;
+            return Ok(());
+        }
+        message.send(output.as_ref())?;
+        let animation = match msg {
+            MsgType::FailureWithCode(code) => Some(self.code_animation(message.get(), code)),
+            _ => message.animation(),
         };
-        debug!("sending {:?} message", msg);
-        message.send()?;
+        let animation = animation
+            .map(|animation| self.guard(animation))
+            .transpose()?;
+        if animation.is_some() || self.decay.is_some() {
+            decay::schedule(output.clone(), message.get(), animation, self.decay);
+        }
         Ok(())
     }
 
-    fn send_failure_msg(&self) -> Result<(), TransitionErr> {
-        self.send_if_present(&MsgType::Failure)?;
+    fn code_animation(&self, failure: BlinkMsg, code: &str) -> Arc<dyn Task> {
+        let (r, g, b) = msg::rgb(failure);
+        let frames = self.failure_code.frames(code, Color::Three(r, g, b));
+        Arc::new(BlinkTask::from_frames(frames))
+    }
+
+    fn send_failure_msg(&self, output: &Arc<dyn Backend>) -> Result<(), TransitionErr> {
+        self.send_if_present(&MsgType::Failure, output)?;
         Ok(())
     }
 
-    fn execute_task_if_present(&self) -> Result<(), TransitionErr> {
+    fn success_suppressed(&self) -> bool {
+        self.quiet_hours
+            .is_some_and(|quiet_hours| quiet_hours.suppresses_success())
+    }
+
+    fn previous(&self) -> Previous {
+        Previous {
+            device: self.backend.current(),
+            lights: self.lights.iter().map(|light| light.current()).collect(),
+        }
+    }
+
+    /// Sends the previous messages directly to the device and the added lights - they were
+    /// already adjusted when they were sent for the first time.
+    fn restore(&self, previous: &Previous) -> Result<(), TransitionErr> {
+        info!("transition cancelled, restoring {:?}", previous);
+        let restored = self.backend.send(previous.device.unwrap_or(BlinkMsg::Off));
+        self.lights
+            .iter()
+            .zip(&previous.lights)
+            .map(|(light, last)| light.send(last.unwrap_or(BlinkMsg::Off)))
+            .fold(restored, Result::and)
+    }
+
+    fn execute_task_if_present(
+        &self,
+        output: &dyn Backend,
+        wait: &dyn Fn(Duration) -> bool,
+    ) -> Result<(), TransitionErr> {
         debug!("executing task");
-        self.task.execute()?;
+        self.task.execute(output, wait)?;
         Ok(())
     }
 
@@ -152,28 +452,353 @@ impl Transition {
         self.failure_msg = Arc::new(ColorMessage::new(color));
         self
     }
+
+    /// Allows to change the outcome color when it gets old.
+    ///
+    /// After `period` from [`notify_success`](super::Notifier::notify_success()) (or
+    /// [`notify_failure`](super::Notifier::notify_failure())), the outcome color is changed as
+    /// described by [`Decay`]. It happens in a background thread, so you don't need to wait for
+    /// it. Starting any transition in the process (also this one again) cancels pending decay.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Decay, Transition};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().stale_after(Duration::from_secs(30 * 60), Decay::Dim);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn stale_after(mut self, period: Duration, decay: Decay) -> Self {
+        self.decay = Some((period, decay));
+        self
+    }
+
+    /// Allows to set the brightness of all colors (`0.0` - off, `1.0` - full brightness, the
+    /// default).
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::Transition;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().brightness(0.5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn brightness(mut self, level: f32) -> Self {
+        self.brightness = level.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Allows to set the time of day when the LED is dimmed further or only failures are
+    /// displayed. See [`QuietHours`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{QuietHours, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().quiet_hours(QuietHours::between(22, 7).failures_only());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn quiet_hours(mut self, quiet_hours: QuietHours) -> Self {
+        self.quiet_hours = Some(quiet_hours);
+        self
+    }
+
+    /// Allows to correct colors for the connected device. See [`Calibration`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Calibration, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().calibration(Calibration::default().gamma(2.2, 2.2, 2.2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    /// Allows to fade between "pending" colors in software.
+    ///
+    /// By default, the device fades between consecutive colors on its own, which produces muddy
+    /// intermediate colors (e.g. blue to orange passes through grey). With interpolation, the
+    /// intermediate colors are computed in a perceptual color space (`OKLab`) and streamed to the
+    /// device. The speed of the change is described by [`Easing`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Easing, Led, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::new(&[Led::Blue, Led::Orange])?.interpolate(Easing::Sine);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn interpolate(mut self, easing: Easing) -> Self {
+        self.task = Arc::new(BlinkTask::interpolated(self.task.get(), easing));
+        self
+    }
+
+    /// Allows to use an [`Effect`] as the "pending" pattern, instead of the colors passed to
+    /// [`new`](Transition::new()).
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Effect, Led, Transition};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let effect = Effect::Heartbeat { color: Led::Blue, period: Duration::from_secs(1) };
+    /// let transition = Transition::default().pending_effect(effect);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn pending_effect(mut self, effect: Effect) -> Self {
+        self.task = Arc::new(BlinkTask::effect(&effect));
+        self
+    }
+
+    /// Allows to display an [`Effect`] on success. The effect is played in background until the
+    /// next transition (any in the process) is started, or the outcome decays (see
+    /// [`stale_after`](Transition::stale_after())).
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Effect, Led, Transition};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let effect = Effect::Breathing { color: Led::Green, period: Duration::from_secs(4) };
+    /// let transition = Transition::default().on_success_effect(effect);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn on_success_effect(mut self, effect: Effect) -> Self {
+        self.success_msg = Arc::new(EffectMessage::new(&effect));
+        self
+    }
+
+    /// Allows to display an [`Effect`] on failure. The effect is played in background until the
+    /// next transition (any in the process) is started, or the outcome decays (see
+    /// [`stale_after`](Transition::stale_after())).
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Effect, Led, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().on_failure_effect(Effect::Candle { color: Led::Red });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn on_failure_effect(mut self, effect: Effect) -> Self {
+        self.failure_msg = Arc::new(EffectMessage::new(&effect));
+        self
+    }
+
+    /// Allows to change the protection from patterns which flash too fast. See [`FlashGuard`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{FlashGuard, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().flash_guard(FlashGuard::new(2.0).reject());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn flash_guard(mut self, guard: FlashGuard) -> Self {
+        self.flash_guard = Some(guard);
+        self
+    }
+
+    /// Disables the protection from patterns which flash too fast. See [`FlashGuard`].
+    ///
+    /// Make sure nobody sensitive to flashing lights can see the LED.
+    #[must_use]
+    pub fn disable_flash_guard(mut self) -> Self {
+        self.flash_guard = None;
+        self
+    }
+
+    /// Allows to choose how the error code passed to
+    /// [`notify_failure_with_code`](super::Notifier::notify_failure_with_code()) is displayed.
+    /// See [`BlinkCode`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{BlinkCode, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().failure_code(BlinkCode::Morse);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn failure_code(mut self, code: BlinkCode) -> Self {
+        self.failure_code = code;
+        self
+    }
+
+    /// Allows to store the "pending" pattern in the device memory and play it there.
+    ///
+    /// The device animates the pattern on its own, so the LED keeps changing colors even when
+    /// the process is killed, and only a few USB writes are needed per transition. The device can
+    /// store up to 32 lines, so long patterns (e.g. [`interpolate`](Transition::interpolate()))
+    /// make [`start`](Transition::start()) fail with [`TransitionErr::PatternTooLong`]. When the
+    /// device has no pattern memory, the pattern is played by the host as usual.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::Transition;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().device_pattern();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn device_pattern(mut self) -> Self {
+        self.device_pattern = true;
+        self
+    }
+
+    /// Allows to detect crashes and hangs of the process using the watchdog of the device.
+    ///
+    /// While the transition is pending, the device is "tickled" in background. When it's not
+    /// tickled within `timeout` (e.g. the process was killed), the device plays `colors` on its
+    /// own, in the same way as the "pending" colors. The watchdog is turned off when the
+    /// transition finishes. Requires the device with pattern memory (blink(1) mk2 or newer),
+    /// other devices ignore this setting. The `timeout` should be between 300 ms and 655 s,
+    /// otherwise [`start`](Transition::start()) fails.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{Led, Transition};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::default().crash_watchdog(Duration::from_secs(10), &[Led::Red, Led::Blank]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn crash_watchdog(mut self, timeout: Duration, colors: &[Led]) -> Self {
+        self.watchdog = Some((timeout, Arc::new(BlinkTask::new(colors))));
+        self
+    }
+
+    /// Allows to display the transition on one more [`Light`], together with the blink(1) (or
+    /// the light passed to [`with_light`](Transition::with_light())) and all lights added
+    /// before.
+    ///
+    /// Every color change is sent to all lights. The `policy` decides what happens when this
+    /// light fails, so e.g. an unreachable lamp bridge doesn't stop the blink(1). Errors of the
+    /// first light always end the transition. Added lights are updated in background, so a slow
+    /// light doesn't hold up the others - it skips the colors it cannot keep up with. When the
+    /// transition finishes, it waits (up to 10 seconds) until all lights display the outcome.
+    /// Patterns are played by the host, so [`device_pattern`](Transition::device_pattern()) has
+    /// no effect.
+    ///
+    /// [`calibration`](Transition::calibration()), [`brightness`](Transition::brightness()) and
+    /// [`quiet_hours`](Transition::quiet_hours()) adjust only the first light, added lights get
+    /// the colors as they are defined.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::error::Error;
+    /// use crate::transition::{ErrorPolicy, Led, LogFile, Terminal, TerminalMode, Transition};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let transition = Transition::with_fallback(&[Led::Blue, Led::Blank])
+    ///     .add_light(Terminal::new(TerminalMode::Title), ErrorPolicy::Log)
+    ///     .add_light(LogFile::new("transition.log"), ErrorPolicy::Ignore);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn add_light<L: Light + 'static>(mut self, light: L, policy: ErrorPolicy) -> Self {
+        let light = Arc::new(LightBackend::new(light));
+        self.lights.push(Arc::new(Sink::new(light, policy)));
+        self
+    }
+}
+
+/// Colors displayed before the transition started, restored when it's cancelled. Added lights
+/// have their own, as they don't get the adjustments of the device.
+#[derive(Debug)]
+struct Previous {
+    device: Option<BlinkMsg>,
+    lights: Vec<Option<BlinkMsg>>,
+}
+
+/// Turns off the watchdog. The outcome is displayed also when it fails - the device plays the
+/// "crashed" pattern only until the outcome color is set.
+fn disarm(keep_alive: Option<KeepAlive>) {
+    if let Some(Err(e)) = keep_alive.map(KeepAlive::disarm) {
+        warn!("failed to disarm watchdog: {}", e);
+    }
 }
 
 impl Default for Transition {
     fn default() -> Self {
-        Self {
-            task: Arc::new(
-                BlinkTask::new(&[Led::Blue, Led::Blank]).expect("failed to create blink task"),
-            ),
-            failure_msg: Arc::new(ColorMessage::new(&Led::Red)),
-            success_msg: Arc::new(ColorMessage::new(&Led::Green)),
-        }
+        Self::new(&[Led::Blue, Led::Blank]).expect("failed to connect to blink(1) device")
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::backend::Backend;
+    use crate::backend::LogBackend;
+    use crate::backend::Tracked;
+    use crate::calibration::Calibration;
+    use crate::code::BlinkCode;
+    use crate::color::Led;
     use crate::error::TransitionErr;
+    use crate::fanout::ErrorPolicy;
+    use crate::flash::FlashGuard;
+    use crate::light::Light;
+    use crate::light::LightBackend;
+    use crate::light::State;
+    use crate::msg::BlinkMsg;
+    use crate::pattern::PatternLine;
     use crate::testutils::utils::init_logging;
     use crate::testutils::utils::MessageSpy;
     use crate::testutils::utils::TaskSpy;
     use crate::transition::Transition;
+    use std::io;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
     use std::sync::Arc;
+    use std::sync::Mutex;
     use std::time::Duration;
 
     #[test]
@@ -226,14 +851,270 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_transition_can_be_started_many_times() -> Result<(), TransitionErr> {
+        init_logging();
+        let (transition, _, failure_msg, success_msg) = transition_with_spies();
+
+        let tx = transition.start()?;
+        tx.notify_failure()?;
+        let tx = transition.start()?;
+        tx.notify_success()?;
+
+        assert!(failure_msg.msg_sent(), "Test failure WAS sent");
+        assert!(success_msg.msg_sent(), "Test success WAS sent");
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_outcome_sent_when_cancelled() -> Result<(), TransitionErr> {
+        init_logging();
+        let (mut transition, task, failure_msg, success_msg) = transition_with_spies();
+        let light = Arc::new(ColorSpy::default());
+        transition.backend = Arc::new(Tracked::new(Arc::new(LightBackend::new(light.clone()))));
+        transition
+            .backend
+            .send(BlinkMsg::Immediate((&Led::Orange).into(), None))?;
+
+        let tx = transition.start()?;
+        std::thread::sleep(Duration::from_millis(1000)); // allow transition to execute
+        light.colors.lock().expect("poisoned lock").clear();
+        tx.cancel()?;
+
+        assert!(task.executed(), "Test task was executed");
+        assert!(!failure_msg.msg_sent(), "Test failure NOT sent");
+        assert!(!success_msg.msg_sent(), "Test success NOT sent");
+        assert_eq!(
+            *light.colors.lock().expect("poisoned lock"),
+            vec![(255, 165, 0)],
+            "Previous color was restored"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_failure_msg_was_sent_when_failure_with_code_notified() -> Result<(), TransitionErr> {
+        init_logging();
+        let (transition, _, failure_msg, success_msg) = transition_with_spies();
+
+        let tx = transition.start()?;
+        tx.notify_failure_with_code(101)?;
+
+        assert!(failure_msg.msg_sent(), "Test failure WAS sent");
+        assert!(!success_msg.msg_sent(), "Test success NOT sent");
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_executed_when_device_cannot_store_patterns() -> Result<(), TransitionErr> {
+        init_logging();
+        let (transition, task, _, success_msg) = transition_with_spies();
+
+        let tx = transition.device_pattern().start()?;
+        std::thread::sleep(Duration::from_millis(1000)); // allow transition to execute
+        tx.notify_success()?;
+
+        assert!(task.executed(), "Test task was executed");
+        assert!(success_msg.msg_sent(), "Test success WAS sent");
+        Ok(())
+    }
+
+    #[derive(Debug, Default)]
+    struct BrokenPatternMemory {
+        disarmed: AtomicBool,
+    }
+
+    impl Backend for BrokenPatternMemory {
+        fn send(&self, _msg: BlinkMsg) -> Result<(), TransitionErr> {
+            Ok(())
+        }
+
+        fn play_pattern(&self, _pattern: &[PatternLine]) -> Result<bool, TransitionErr> {
+            Err(io::Error::other("pattern rejected").into())
+        }
+
+        fn arm_watchdog(
+            &self,
+            _timeout: Duration,
+            _pattern: &[PatternLine],
+        ) -> Result<bool, TransitionErr> {
+            Ok(true)
+        }
+
+        fn disarm_watchdog(&self) -> Result<(), TransitionErr> {
+            self.disarmed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_watchdog_disarmed_when_start_fails() {
+        init_logging();
+        let (mut transition, _, _, _) = transition_with_spies();
+        let device = Arc::new(BrokenPatternMemory::default());
+        transition.backend = device.clone();
+        let transition = transition
+            .device_pattern()
+            .crash_watchdog(Duration::from_secs(1), &[Led::Red]);
+
+        assert!(transition.start().is_err(), "Pattern was rejected");
+        assert!(
+            device.disarmed.load(Ordering::SeqCst),
+            "Watchdog was disarmed"
+        );
+    }
+
+    #[derive(Debug, Default)]
+    struct StateSpy {
+        states: Mutex<Vec<State>>,
+    }
+
+    impl Light for StateSpy {
+        fn set(&self, _rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+            Ok(())
+        }
+
+        fn state(&self, state: State) -> Result<(), TransitionErr> {
+            self.states.lock().expect("poisoned lock").push(state);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_light_is_informed_about_state() -> Result<(), TransitionErr> {
+        init_logging();
+        let spy = Arc::new(StateSpy::default());
+        let transition = Transition::with_light(&[Led::Blue, Led::Blank], spy.clone());
+
+        transition.start()?.notify_failure_with_code(7)?;
+        transition.start()?.cancel()?;
+
+        assert_eq!(
+            *spy.states.lock().expect("poisoned lock"),
+            vec![
+                State::Pending,
+                State::Failure,
+                State::Pending,
+                State::Cancelled
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_added_lights_get_all_changes() -> Result<(), TransitionErr> {
+        init_logging();
+        let first = Arc::new(StateSpy::default());
+        let second = Arc::new(StateSpy::default());
+        let transition = Transition::with_light(&[Led::Blue, Led::Blank], first.clone())
+            .add_light(second.clone(), ErrorPolicy::Abort);
+
+        transition.start()?.notify_success()?;
+
+        for spy in [first, second] {
+            assert_eq!(
+                *spy.states.lock().expect("poisoned lock"),
+                vec![State::Pending, State::Success]
+            );
+        }
+        Ok(())
+    }
+
+    #[derive(Debug, Default)]
+    struct ColorSpy {
+        colors: Mutex<Vec<(u8, u8, u8)>>,
+    }
+
+    impl Light for ColorSpy {
+        fn set(&self, rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+            self.colors.lock().expect("poisoned lock").push(rgb);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_added_lights_are_not_adjusted() -> Result<(), TransitionErr> {
+        init_logging();
+        let device = Arc::new(ColorSpy::default());
+        let lamp = Arc::new(ColorSpy::default());
+        let transition = Transition::with_light(&[Led::Blue, Led::Blank], device.clone())
+            .calibration(Calibration::default().gain(0.5, 0.5, 0.5))
+            .brightness(0.5)
+            .add_light(lamp.clone(), ErrorPolicy::Abort);
+
+        transition.start()?.notify_failure()?;
+
+        let device_colors = device.colors.lock().expect("poisoned lock");
+        assert_ne!(device_colors.last(), Some(&(255, 0, 0)));
+        assert_eq!(
+            lamp.colors.lock().expect("poisoned lock").last(),
+            Some(&(255, 0, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_added_lights_restore_their_own_color() -> Result<(), TransitionErr> {
+        init_logging();
+        let device = Arc::new(ColorSpy::default());
+        let lamp = Arc::new(ColorSpy::default());
+        let transition = Transition::with_light(&[Led::Blue, Led::Blank], device.clone())
+            .brightness(0.5)
+            .add_light(lamp.clone(), ErrorPolicy::Abort);
+        transition.start()?.notify_failure()?;
+        let device_color = device.colors.lock().expect("poisoned lock").last().copied();
+
+        let notifier = transition.start()?;
+        std::thread::sleep(Duration::from_millis(100));
+        notifier.cancel()?;
+
+        assert_eq!(
+            device.colors.lock().expect("poisoned lock").last().copied(),
+            device_color
+        );
+        assert_eq!(
+            lamp.colors.lock().expect("poisoned lock").last(),
+            Some(&(255, 0, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_failure_msg_was_sent_after_panic() -> Result<(), TransitionErr> {
+        init_logging();
+        let (mut transition, _, failure_msg, success_msg) = transition_with_spies();
+        let spy = Arc::new(StateSpy::default());
+        transition.backend = Arc::new(LightBackend::new(spy.clone()));
+
+        transition.panicked(None, &transition.output())?;
+
+        assert!(failure_msg.msg_sent(), "Test failure WAS sent");
+        assert!(!success_msg.msg_sent(), "Test success NOT sent");
+        assert_eq!(
+            *spy.states.lock().expect("poisoned lock"),
+            vec![State::Failure]
+        );
+        Ok(())
+    }
+
     fn transition_with_spies() -> (Transition, Arc<TaskSpy>, Arc<MessageSpy>, Arc<MessageSpy>) {
         let task = Arc::new(TaskSpy::new());
         let failure_msg = Arc::new(MessageSpy::new());
         let success_msg = Arc::new(MessageSpy::new());
         let transition = Transition {
+            backend: Arc::new(LogBackend),
             task: task.clone(),
             failure_msg: failure_msg.clone(),
             success_msg: success_msg.clone(),
+            decay: None,
+            brightness: 1.0,
+            quiet_hours: None,
+            calibration: None,
+            flash_guard: Some(FlashGuard::default()),
+            failure_code: BlinkCode::default(),
+            device_pattern: false,
+            watchdog: None,
+            lights: Vec::new(),
         };
         (transition, task, failure_msg, success_msg)
     }
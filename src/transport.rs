@@ -0,0 +1,92 @@
+use crate::error::TransitionErr;
+use rusb::Context;
+use rusb::DeviceHandle;
+use rusb::UsbContext;
+use std::fmt;
+use std::fmt::Debug;
+use std::time::Duration;
+
+const VENDOR_ID: u16 = 0x27B8;
+const PRODUCT_ID: u16 = 0x01ED;
+
+pub(crate) const REPORT_ID: u8 = 1;
+pub(crate) const REPORT_SIZE: usize = 9;
+
+const GET_REPORT: u8 = 0x01;
+const SET_REPORT: u8 = 0x09;
+const FEATURE_REPORT: u16 = 0x0300 | REPORT_ID as u16;
+const HOST_TO_INTERFACE: u8 = 0x21;
+const INTERFACE_TO_HOST: u8 = 0xA1;
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// HID feature report - the report id, the command and its arguments.
+pub(crate) type Report = [u8; REPORT_SIZE];
+
+/// Carries feature reports between the host and one blink(1) device.
+pub(crate) trait Transport: Send + Sync + Debug {
+    fn write(&self, report: &Report) -> Result<(), TransitionErr>;
+
+    /// Reads the response to the last written report.
+    fn read(&self) -> Result<Report, TransitionErr>;
+
+    fn serial(&self) -> Result<String, TransitionErr>;
+}
+
+/// Talks to the device with control transfers, using libusb.
+pub(crate) struct UsbTransport {
+    handle: DeviceHandle<Context>,
+}
+
+impl UsbTransport {
+    /// Opens all connected blink(1) devices.
+    pub(crate) fn open_all() -> Result<Vec<Self>, TransitionErr> {
+        let mut devices = Vec::new();
+        for device in Context::new()?.devices()?.iter() {
+            let descriptor = device.device_descriptor()?;
+            if descriptor.vendor_id() == VENDOR_ID && descriptor.product_id() == PRODUCT_ID {
+                devices.push(Self {
+                    handle: device.open()?,
+                });
+            }
+        }
+        Ok(devices)
+    }
+}
+
+impl Transport for UsbTransport {
+    fn write(&self, report: &Report) -> Result<(), TransitionErr> {
+        self.handle.write_control(
+            HOST_TO_INTERFACE,
+            SET_REPORT,
+            FEATURE_REPORT,
+            0,
+            report,
+            TIMEOUT,
+        )?;
+        Ok(())
+    }
+
+    fn read(&self) -> Result<Report, TransitionErr> {
+        let mut report = [0; REPORT_SIZE];
+        self.handle.read_control(
+            INTERFACE_TO_HOST,
+            GET_REPORT,
+            FEATURE_REPORT,
+            0,
+            &mut report,
+            TIMEOUT,
+        )?;
+        Ok(report)
+    }
+
+    fn serial(&self) -> Result<String, TransitionErr> {
+        let descriptor = self.handle.device().device_descriptor()?;
+        Ok(self.handle.read_serial_number_string_ascii(&descriptor)?)
+    }
+}
+
+impl Debug for UsbTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UsbTransport").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,184 @@
+use crate::backend::Backend;
+use crate::error::TransitionErr;
+use crate::pattern::PatternLine;
+use crossbeam_channel::bounded;
+use crossbeam_channel::RecvTimeoutError;
+use crossbeam_channel::Sender;
+use log::debug;
+use log::warn;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Number of tickles sent within one timeout, so a single late tickle doesn't trigger the
+/// "crashed" pattern.
+const TICKLES_PER_TIMEOUT: u32 = 3;
+
+/// Shortest timeout, so the device is tickled at most every 100 ms instead of flooding the USB.
+const MIN_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Longest timeout the device can store (65535 units of 10 ms).
+const MAX_TIMEOUT: Duration = Duration::from_millis(655_350);
+
+/// Keeps tickling the device while the transition is pending. The watchdog is disarmed also
+/// when it's dropped, e.g. when the transition fails to start.
+#[derive(Debug)]
+pub(crate) struct KeepAlive {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+    backend: Arc<dyn Backend>,
+}
+
+impl KeepAlive {
+    /// Stops tickling and disarms the watchdog, so the device doesn't switch to the "crashed"
+    /// pattern.
+    pub(crate) fn disarm(mut self) -> Result<(), TransitionErr> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<(), TransitionErr> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+        drop(self.stop.take());
+        if handle.join().is_err() {
+            warn!("watchdog thread panicked");
+        }
+        debug!("disarming watchdog");
+        self.backend.disarm_watchdog()
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            warn!("failed to disarm watchdog: {}", e);
+        }
+    }
+}
+
+/// Stores the "crashed" `pattern` in the device, which plays it when it's not tickled within
+/// `timeout`. Returns `None` when the device doesn't support the watchdog.
+pub(crate) fn arm(
+    backend: Arc<dyn Backend>,
+    timeout: Duration,
+    pattern: &[PatternLine],
+) -> Result<Option<KeepAlive>, TransitionErr> {
+    if !(MIN_TIMEOUT..=MAX_TIMEOUT).contains(&timeout) {
+        return Err(TransitionErr::WatchdogTimeout {
+            timeout,
+            min: MIN_TIMEOUT,
+            max: MAX_TIMEOUT,
+        });
+    }
+    if !backend.arm_watchdog(timeout, pattern)? {
+        debug!("device does not support the watchdog");
+        return Ok(None);
+    }
+    let (stop, stopped) = bounded::<()>(0);
+    let interval = timeout / TICKLES_PER_TIMEOUT;
+    let tickled = backend.clone();
+    let handle = thread::spawn(move || {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            if let Err(e) = tickled.tickle_watchdog() {
+                warn!("failed to tickle watchdog: {}", e);
+            }
+        }
+    });
+    Ok(Some(KeepAlive {
+        stop: Some(stop),
+        handle: Some(handle),
+        backend,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::msg::BlinkMsg;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    #[derive(Debug, Default)]
+    struct WatchdogSpy {
+        tickles: AtomicUsize,
+        disarmed: AtomicBool,
+    }
+
+    impl Backend for WatchdogSpy {
+        fn send(&self, _msg: BlinkMsg) -> Result<(), TransitionErr> {
+            Ok(())
+        }
+
+        fn arm_watchdog(
+            &self,
+            _timeout: Duration,
+            _pattern: &[PatternLine],
+        ) -> Result<bool, TransitionErr> {
+            Ok(true)
+        }
+
+        fn tickle_watchdog(&self) -> Result<(), TransitionErr> {
+            self.tickles.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn disarm_watchdog(&self) -> Result<(), TransitionErr> {
+            self.disarmed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_device_is_tickled_until_disarmed() -> Result<(), TransitionErr> {
+        let spy = Arc::new(WatchdogSpy::default());
+
+        let keep_alive = arm(spy.clone(), MIN_TIMEOUT, &[])?.expect("watchdog was not armed");
+        thread::sleep(Duration::from_millis(700));
+        keep_alive.disarm()?;
+        let tickles = spy.tickles.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(200));
+
+        assert!(tickles >= 5, "Device was tickled only {tickles} times");
+        assert_eq!(spy.tickles.load(Ordering::SeqCst), tickles);
+        assert!(spy.disarmed.load(Ordering::SeqCst), "Watchdog was disarmed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_dropped_keep_alive_disarms_watchdog() -> Result<(), TransitionErr> {
+        let spy = Arc::new(WatchdogSpy::default());
+
+        drop(arm(spy.clone(), MIN_TIMEOUT, &[])?);
+
+        assert!(spy.disarmed.load(Ordering::SeqCst), "Watchdog was disarmed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_timeout_is_rejected() {
+        let spy = Arc::new(WatchdogSpy::default());
+
+        for timeout in [Duration::ZERO, Duration::from_secs(3600)] {
+            assert!(matches!(
+                arm(spy.clone(), timeout, &[]),
+                Err(TransitionErr::WatchdogTimeout { .. })
+            ));
+        }
+        assert_eq!(
+            spy.tickles.load(Ordering::SeqCst),
+            0,
+            "Watchdog was not armed"
+        );
+    }
+
+    #[test]
+    fn test_watchdog_not_armed_when_unsupported() -> Result<(), TransitionErr> {
+        let backend = Arc::new(crate::backend::LogBackend);
+
+        assert!(arm(backend, Duration::from_secs(1), &[])?.is_none());
+        Ok(())
+    }
+}
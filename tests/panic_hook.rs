@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use transition::{install_panic_hook, Led, Light, State, Transition, TransitionErr};
+
+#[derive(Debug, Default)]
+struct LightSpy {
+    colors: Mutex<Vec<(u8, u8, u8)>>,
+    states: Mutex<Vec<State>>,
+}
+
+impl Light for LightSpy {
+    fn set(&self, rgb: (u8, u8, u8), _fade: Duration) -> Result<(), TransitionErr> {
+        self.colors.lock().expect("poisoned lock").push(rgb);
+        Ok(())
+    }
+
+    fn state(&self, state: State) -> Result<(), TransitionErr> {
+        self.states.lock().expect("poisoned lock").push(state);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_panic_in_other_thread_fails_transition() -> Result<(), TransitionErr> {
+    install_panic_hook(None);
+    let spy = Arc::new(LightSpy::default());
+    let colors = [Led::Blue, Led::Blank, Led::Blue, Led::Blank]; // 2 s cycle
+    let notifier = Transition::with_light(&colors, spy.clone()).start()?;
+    thread::sleep(Duration::from_millis(100));
+
+    let worker = thread::spawn(|| panic!("worker crashed"));
+
+    assert!(worker.join().is_err(), "Worker panicked");
+    assert_eq!(
+        spy.colors.lock().expect("poisoned lock").last(),
+        Some(&(255, 0, 0))
+    );
+    assert_eq!(
+        spy.states.lock().expect("poisoned lock").last(),
+        Some(&State::Failure)
+    );
+    assert!(
+        notifier.notify_success().is_err(),
+        "Transition already over"
+    );
+    Ok(())
+}